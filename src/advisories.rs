@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// A content advisory for a genre, so that it can be labelled in the frontend.
+pub enum Advisory {
+    /// Music that promotes hatred of a group (i.e. "Nazi punk")
+    HateMusic,
+    /// Music tied to extremist politics, without being hate music itself
+    Extremist,
+}
+
+/// Derives an advisory from a genre's categories.
+///
+/// Pages that this gets wrong should be overridden in [`crate::data_patches::advisories`].
+pub fn from_categories(categories: &[String]) -> Option<Advisory> {
+    const HATE_MUSIC_KEYWORDS: &[&str] = &[
+        "hate music",
+        "neo-nazi",
+        "nazi music",
+        "white power",
+        "white supremacist",
+        "antisemitic",
+    ];
+    const EXTREMIST_KEYWORDS: &[&str] = &["far-right", "fascist", "extremism", "extremist"];
+
+    let has_any = |keywords: &[&str]| {
+        categories.iter().any(|category| {
+            let category = category.to_lowercase();
+            keywords
+                .iter()
+                .any(|keyword| contains_keyword(&category, keyword))
+        })
+    };
+    if has_any(HATE_MUSIC_KEYWORDS) {
+        Some(Advisory::HateMusic)
+    } else if has_any(EXTREMIST_KEYWORDS) {
+        Some(Advisory::Extremist)
+    } else {
+        None
+    }
+}
+
+/// Whether `keyword` is in `category` as a whole word (or its plural), and isn't something
+/// being opposed, as in "Anti-fascist music".
+fn contains_keyword(category: &str, keyword: &str) -> bool {
+    category.match_indices(keyword).any(|(index, _)| {
+        let before = &category[..index];
+        let after = &category[index + keyword.len()..];
+        let after = after.strip_prefix('s').unwrap_or(after);
+        !before.ends_with(|c: char| c.is_alphanumeric())
+            && !after.starts_with(|c: char| c.is_alphanumeric())
+            && !before.ends_with("anti-")
+            && !before.ends_with("anti ")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_categories() {
+        let categories =
+            |categories: &[&str]| categories.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            from_categories(&categories(&["Punk rock genres", "Neo-Nazi music"])),
+            Some(Advisory::HateMusic)
+        );
+        assert_eq!(
+            from_categories(&categories(&["Far-right politics and music"])),
+            Some(Advisory::Extremist)
+        );
+        assert_eq!(
+            from_categories(&categories(&["Punk rock genres", "Oi!"])),
+            None
+        );
+        assert_eq!(
+            from_categories(&categories(&["Fascist music", "Songs about extremists"])),
+            Some(Advisory::Extremist)
+        );
+        assert_eq!(
+            from_categories(&categories(&[
+                "Anti-fascist music",
+                "Anti-racist music",
+                "Anti-extremism in music",
+                "Antifascist songs",
+            ])),
+            None
+        );
+    }
+}
@@ -0,0 +1,334 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::Context;
+use parse_wiki_text_2 as pwt;
+use serde::{Deserialize, Serialize};
+use wikitext_util::{
+    nodes_inner_text, nodes_inner_wikitext, pwt_configuration, InnerTextConfig, NodeMetadata,
+};
+
+use crate::{
+    citations, remove_comments_from_wikitext_the_painful_way, sanitize_page_name, GenrePages,
+    PageName, ProcessedGenres, WikitextHeader,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+/// A genre's article as a tree of sections, so that it can be read in full offline. This is
+/// written to its own file, as it's far larger than the rest of a genre's data.
+pub struct ArticleSections {
+    pub page: PageName,
+    /// The citations of the whole article; sections refer to them by index
+    pub references: Vec<citations::Citation>,
+    /// The lead for a genre that's the subject of its article, or the genre's heading
+    pub root: Section,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Section {
+    /// The heading of the section; the lead doesn't have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    /// The level of the heading (i.e. 2 for `== History ==`); the lead is level 1
+    pub level: u8,
+    /// The section's own wikitext, without its refs, tables or subsections
+    pub wikitext: String,
+    /// The indices of the references cited by the section's wikitext
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tables: Vec<Table>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Section>,
+}
+impl Section {
+    /// Finds the first section in this tree with `heading`.
+    fn find(&self, heading: &str) -> Option<&Section> {
+        if self.heading.as_deref() == Some(heading) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(heading))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A table from a section. These are kept apart from the section's wikitext as the frontend
+/// doesn't render wikitext tables.
+pub struct Table {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    pub rows: Vec<Vec<TableCell>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TableCell {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub heading: bool,
+    pub wikitext: String,
+}
+
+/// Writes the section tree of every genre to `<sanitized page name>.json` in `output_path`,
+/// replacing any from a previous run.
+pub fn produce(
+    start: std::time::Instant,
+    output_path: &Path,
+    genres: &GenrePages,
+    processed_genres: &ProcessedGenres,
+) -> anyhow::Result<()> {
+    if output_path.is_dir() {
+        std::fs::remove_dir_all(output_path)
+            .context("Failed to remove previous article sections")?;
+    }
+    std::fs::create_dir_all(output_path)?;
+
+    // Several genres can be on the same article, so each article is only parsed once
+    let mut genres_by_article: HashMap<PageName, Vec<&PageName>> = HashMap::new();
+    for page in processed_genres.0.keys() {
+        genres_by_article
+            .entry(PageName::new(page.name.clone(), None))
+            .or_default()
+            .push(page);
+    }
+
+    let pwt_configuration = pwt_configuration();
+    let mut count = 0;
+    for (article, pages) in genres_by_article {
+        let path = genres
+            .0
+            .get(&article)
+            .with_context(|| format!("Missing wikitext for {article}"))?;
+        let wikitext = std::fs::read_to_string(path)?;
+        let (wikitext_header, wikitext) = wikitext
+            .split_once('\n')
+            .with_context(|| format!("Missing header for {article}"))?;
+        let _: WikitextHeader = serde_json::from_str(wikitext_header)?;
+        let wikitext = remove_comments_from_wikitext_the_painful_way(
+            &pwt_configuration,
+            None,
+            &article,
+            wikitext,
+        );
+        let Ok(parsed_wikitext) =
+            pwt_configuration.parse_with_timeout(&wikitext, std::time::Duration::from_secs(5))
+        else {
+            eprintln!("Failed to parse {article} for its sections");
+            continue;
+        };
+        let (references, lead) =
+            section_tree(&pwt_configuration, &wikitext, &parsed_wikitext.nodes);
+
+        for page in pages {
+            let root = match &page.heading {
+                Some(heading) => match lead.find(heading) {
+                    Some(section) => section.clone(),
+                    None => continue,
+                },
+                None => lead.clone(),
+            };
+            std::fs::write(
+                output_path.join(format!("{}.json", sanitize_page_name(page))),
+                serde_json::to_string(&ArticleSections {
+                    page: page.clone(),
+                    references: references.clone(),
+                    root,
+                })?,
+            )
+            .with_context(|| format!("Failed to write article sections for {page}"))?;
+            count += 1;
+        }
+    }
+
+    println!(
+        "{:.2}s: wrote article sections for {count} genres",
+        start.elapsed().as_secs_f32()
+    );
+    Ok(())
+}
+
+/// Builds the section tree of an article from its top-level nodes, returning the citations
+/// of the article alongside the lead.
+fn section_tree(
+    pwt_configuration: &pwt::Configuration,
+    wikitext: &str,
+    nodes: &[pwt::Node],
+) -> (Vec<citations::Citation>, Section) {
+    let named_refs = citations::collect_named_refs(wikitext);
+    let mut references = vec![];
+    // The sections that are still open, from the lead to the innermost
+    let mut stack = vec![Section {
+        level: 1,
+        ..Default::default()
+    }];
+    // The start of the wikitext that hasn't been added to a section yet
+    let mut cursor = 0;
+    let mut in_ref = false;
+
+    fn close_section(stack: &mut Vec<Section>) {
+        let section = stack.pop().unwrap();
+        stack.last_mut().unwrap().children.push(section);
+    }
+
+    for node in nodes {
+        let metadata = NodeMetadata::for_node(node);
+        if in_ref {
+            if matches!(node, pwt::Node::EndTag { name, .. } if name == "ref") {
+                in_ref = false;
+                cursor = metadata.end;
+            }
+            continue;
+        }
+
+        let is_excluded = match node {
+            pwt::Node::Heading { .. }
+            | pwt::Node::Table { .. }
+            | pwt::Node::Category { .. }
+            | pwt::Node::Comment { .. } => true,
+            pwt::Node::Tag { name, .. } | pwt::Node::StartTag { name, .. } => {
+                name == "ref" || name == "references"
+            }
+            pwt::Node::EndTag { name, .. } => name == "references",
+            pwt::Node::Template { name, .. } => {
+                let name = nodes_inner_text(name, &InnerTextConfig::default()).to_lowercase();
+                name.starts_with("infobox")
+                    || name.starts_with("use ")
+                    || name == "short description"
+                    || name == "reflist"
+                    || name == "authority control"
+            }
+            _ => false,
+        };
+        if !is_excluded {
+            continue;
+        }
+
+        let section = stack.last_mut().unwrap();
+        section
+            .wikitext
+            .push_str(&wikitext[cursor.min(metadata.start)..metadata.start]);
+        cursor = metadata.end;
+
+        match node {
+            pwt::Node::Heading { level, nodes, .. } => {
+                while stack.last().is_some_and(|s| s.level >= *level) && stack.len() > 1 {
+                    close_section(&mut stack);
+                }
+                stack.push(Section {
+                    heading: Some(nodes_inner_text(nodes, &InnerTextConfig::default())),
+                    level: *level,
+                    ..Default::default()
+                });
+            }
+            pwt::Node::Table { captions, rows, .. } => {
+                section.tables.push(Table {
+                    caption: captions
+                        .first()
+                        .map(|caption| nodes_inner_wikitext(wikitext, &caption.content))
+                        .filter(|caption| !caption.trim().is_empty()),
+                    rows: rows
+                        .iter()
+                        .filter(|row| !row.cells.is_empty())
+                        .map(|row| {
+                            row.cells
+                                .iter()
+                                .map(|cell| TableCell {
+                                    heading: matches!(cell.type_, pwt::TableCellType::Heading),
+                                    wikitext: nodes_inner_wikitext(wikitext, &cell.content)
+                                        .trim()
+                                        .to_string(),
+                                })
+                                .collect()
+                        })
+                        .collect(),
+                });
+            }
+            pwt::Node::Tag { name, start, .. } | pwt::Node::StartTag { name, start, .. }
+                if name == "ref" =>
+            {
+                // Reused refs (`<ref name="x" />`) don't have an end tag
+                in_ref = matches!(node, pwt::Node::StartTag { .. })
+                    && !wikitext[metadata.start..metadata.end].ends_with("/>");
+                if let Some(citation) = citations::Citation::from_ref_at(
+                    pwt_configuration,
+                    wikitext,
+                    *start,
+                    &named_refs,
+                ) {
+                    let index = citations::add_reference(&mut references, citation);
+                    if !section.references.contains(&index) {
+                        section.references.push(index);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    stack
+        .last_mut()
+        .unwrap()
+        .wikitext
+        .push_str(&wikitext[cursor.min(wikitext.len())..]);
+    while stack.len() > 1 {
+        close_section(&mut stack);
+    }
+
+    let mut lead = stack.pop().unwrap();
+    trim_wikitext(&mut lead);
+    (references, lead)
+}
+
+fn trim_wikitext(section: &mut Section) {
+    section.wikitext = section.wikitext.trim().to_string();
+    for child in &mut section.children {
+        trim_wikitext(child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_section_tree() {
+        let pwt_configuration = pwt_configuration();
+        let wikitext = remove_comments_from_wikitext_the_painful_way(
+            &pwt_configuration,
+            None,
+            &PageName::new("Dub music", None),
+            "{{Short description|Genre}}\n'''Dub''' is a genre.<ref>A</ref>\n== History ==<!-- x -->\nIt emerged in Jamaica.<ref name=\"b\">B</ref>\n=== 1970s ===\nIt grew.<ref name=\"b\" />\n{| class=\"wikitable\"\n! Year !! Event\n|-\n| 1973 || [[King Tubby]]\n|}\n== Legacy ==\nIt influenced [[dubstep]].\n[[Category:Reggae genres]]",
+        );
+        let parsed_wikitext = pwt_configuration.parse(&wikitext).unwrap();
+        let (references, lead) =
+            section_tree(&pwt_configuration, &wikitext, &parsed_wikitext.nodes);
+
+        assert_eq!(references.len(), 2);
+        assert_eq!(lead.wikitext, "'''Dub''' is a genre.");
+        assert_eq!(lead.references, vec![0]);
+        assert_eq!(
+            lead.children
+                .iter()
+                .map(|s| (s.heading.as_deref(), s.level))
+                .collect::<Vec<_>>(),
+            vec![(Some("History"), 2), (Some("Legacy"), 2)]
+        );
+
+        let history = &lead.children[0];
+        assert_eq!(history.wikitext, "It emerged in Jamaica.");
+        let seventies = &history.children[0];
+        assert_eq!(seventies.wikitext, "It grew.");
+        assert_eq!(seventies.references, vec![1]);
+        assert_eq!(
+            seventies.tables[0]
+                .rows
+                .iter()
+                .map(|row| row
+                    .iter()
+                    .map(|c| (c.heading, c.wikitext.as_str()))
+                    .collect::<Vec<_>>())
+                .collect::<Vec<_>>(),
+            vec![
+                vec![(true, "Year"), (true, "Event")],
+                vec![(false, "1973"), (false, "[[King Tubby]]")],
+            ]
+        );
+        assert_eq!(lead.children[1].wikitext, "It influenced [[dubstep]].");
+    }
+}
@@ -0,0 +1,237 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+use crate::{for_each_page, PageName};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+/// A WikiProject quality class, ordered from lowest to highest. Classes that don't describe
+/// an article's quality (i.e. "List" or "Redirect") aren't recorded.
+pub enum AssessmentClass {
+    Stub,
+    Start,
+    C,
+    B,
+    GA,
+    A,
+    FA,
+}
+impl AssessmentClass {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "stub" => Some(Self::Stub),
+            "start" => Some(Self::Start),
+            "c" => Some(Self::C),
+            "b" => Some(Self::B),
+            "ga" => Some(Self::GA),
+            "a" => Some(Self::A),
+            "fa" => Some(Self::FA),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+/// A WikiProject importance rating, ordered from lowest to highest.
+pub enum Importance {
+    Low,
+    Mid,
+    High,
+    Top,
+}
+impl Importance {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "mid" => Some(Self::Mid),
+            "high" => Some(Self::High),
+            "top" => Some(Self::Top),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+/// The assessment of an article by the WikiProjects on its talk page. Each is the highest
+/// given by any of the projects.
+pub struct Assessment {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<AssessmentClass>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub importance: Option<Importance>,
+}
+
+/// The assessments of every genre article, keyed by the article (not the talk page).
+#[derive(Default)]
+pub struct Assessments(pub HashMap<PageName, Assessment>);
+impl Assessments {
+    /// The assessment of the article `page` is on; genres under a heading share the
+    /// assessment of their article.
+    pub fn get(&self, page: &PageName) -> Option<Assessment> {
+        self.0.get(&PageName::new(page.name.clone(), None)).copied()
+    }
+}
+
+/// The shortcuts for WikiProject banners and the banner shell, lowercase and without spaces.
+/// Any other template starting with "WP" isn't a banner.
+const BANNER_SHORTCUTS: &[&str] = &[
+    "wpb",
+    "wpbs",
+    "wpbannershell",
+    "wpmusic",
+    "wpmusicgenres",
+    "wpjazz",
+    "wpmetal",
+    "wphiphop",
+    "wprock",
+    "wpelectronicmusic",
+];
+
+/// Whether a template is a WikiProject banner, given its lowercase name.
+fn is_banner(name: &str) -> bool {
+    name.starts_with("wikiproject") || BANNER_SHORTCUTS.contains(&name.replace(' ', "").as_str())
+}
+
+/// The lowercase name of the template that `rest` starts with, after its `{{`.
+fn template_name(rest: &str) -> String {
+    let end = rest.find(['|', '}']).unwrap_or(rest.len());
+    rest[..end].trim().to_lowercase()
+}
+
+/// Reads the assessments of `pages` from their talk pages in the `pages-meta-current` dump
+/// files, or loads them from `assessments_path` if they've already been read.
+///
+/// The `pages-articles` dump that everything else comes from doesn't include talk pages, so
+/// assessments are skipped if no talk page dump is configured.
+pub fn load_or_extract<'a>(
+    start: std::time::Instant,
+    dump_paths: &[PathBuf],
+    assessments_path: &Path,
+    pages: impl Iterator<Item = &'a PageName>,
+) -> anyhow::Result<Assessments> {
+    if assessments_path.is_file() {
+        let assessments: HashMap<PageName, Assessment> =
+            toml::from_str(&std::fs::read_to_string(assessments_path)?)?;
+        // Assessments used to be read from the `pages-articles` dump, which has no talk pages,
+        // so an empty file is re-extracted if there's a talk page dump to read
+        if !assessments.is_empty() || dump_paths.is_empty() {
+            println!(
+                "{:.2}s: loaded {} assessments",
+                start.elapsed().as_secs_f32(),
+                assessments.len()
+            );
+            return Ok(Assessments(assessments));
+        }
+    }
+    if dump_paths.is_empty() {
+        println!("No talk page dump configured, skipping assessments");
+        return Ok(Assessments::default());
+    }
+
+    // Only the talk pages of these articles are read, as there are millions of talk pages
+    let articles = pages.map(|page| page.name.as_str()).collect::<HashSet<_>>();
+    let assessments = dump_paths
+        .par_iter()
+        .map(|path| {
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("Failed to open talk page dump {path:?}"))?;
+            let mut assessments = HashMap::new();
+            for_each_page(
+                std::io::BufReader::new(bzip2::read::MultiBzDecoder::new(std::io::BufReader::new(
+                    file,
+                ))),
+                |title, _timestamp, text| {
+                    let Some(article) = title
+                        .strip_prefix("Talk:")
+                        .filter(|article| articles.contains(article))
+                    else {
+                        return;
+                    };
+                    if let Some(assessment) = from_talk_page(text) {
+                        assessments.insert(PageName::new(article, None), assessment);
+                    }
+                },
+            );
+            anyhow::Ok(assessments)
+        })
+        .try_reduce(HashMap::new, |mut a, b| {
+            a.extend(b);
+            Ok(a)
+        })?;
+    anyhow::ensure!(
+        !assessments.is_empty(),
+        "No assessments were found in the talk page dump; is it a `pages-meta-current` dump?"
+    );
+
+    std::fs::write(
+        assessments_path,
+        toml::to_string_pretty(&assessments)?.as_bytes(),
+    )
+    .context("Failed to write assessments")?;
+    println!(
+        "{:.2}s: extracted {} assessments from talk pages",
+        start.elapsed().as_secs_f32(),
+        assessments.len()
+    );
+
+    Ok(Assessments(assessments))
+}
+
+/// Reads the class and importance from every WikiProject banner on a talk page, including
+/// the class given to `{{WikiProject banner shell}}`.
+pub fn from_talk_page(text: &str) -> Option<Assessment> {
+    let mut assessment = Assessment::default();
+    // Banners are nested in the banner shell, so each template is only read up to the next
+    // template or the end of the current one
+    for rest in text.split("{{").skip(1) {
+        if !is_banner(&template_name(rest)) {
+            continue;
+        }
+        let template = &rest[..rest.find("}}").unwrap_or(rest.len())];
+        for parameter in template.split('|').skip(1) {
+            let Some((key, value)) = parameter.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "class" => assessment.class = assessment.class.max(AssessmentClass::parse(value)),
+                "importance" => {
+                    assessment.importance = assessment.importance.max(Importance::parse(value))
+                }
+                _ => {}
+            }
+        }
+    }
+    (assessment != Assessment::default()).then_some(assessment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_talk_page() {
+        assert_eq!(
+            from_talk_page(
+                "{{Talk header}}\n{{WikiProject banner shell|class=C|\n{{WikiProject Music genres|importance=mid}}\n{{WikiProject Jazz|class=B|importance=High}}\n{{WikiProject Lists|class=List}}\n}}\n== Origins ==\nThe class=FA claim here isn't a banner."
+            ),
+            Some(Assessment {
+                class: Some(AssessmentClass::B),
+                importance: Some(Importance::High),
+            })
+        );
+        assert_eq!(from_talk_page("{{Talk header}}\nNo banners here."), None);
+        assert_eq!(
+            from_talk_page("{{WPBS|\n{{WP Jazz|importance=low}}\n}}\n{{WPTalk|class=FA}}"),
+            Some(Assessment {
+                class: None,
+                importance: Some(Importance::Low),
+            })
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use parse_wiki_text_2 as pwt;
+
+use crate::{stubs::Stubs, PageName, ProcessedGenres};
+
+/// The parent categories of every music-related category, without the `Category:` prefix.
+#[derive(Default)]
+pub struct CategoryParents(pub HashMap<String, Vec<String>>);
+
+/// The tags derived from the categories of each genre and stub.
+#[derive(Default)]
+pub struct CategoryTags(pub HashMap<PageName, BTreeSet<String>>);
+
+/// How far up the category tree to look for a root; the tree is not strictly a hierarchy,
+/// so walking too far leads to unrelated categories.
+const MAX_DEPTH: usize = 4;
+
+/// The default roots used if `category_roots` isn't set in `config.toml`.
+pub fn default_roots() -> Vec<String> {
+    [
+        "Music genres by country",
+        "Music genres by decade",
+        "Electronic music genres",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+/// Whether a category from the dump is worth keeping for the category tree. Only categories
+/// about music or genres are kept, as the full tree is very large.
+pub fn is_relevant_category(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("music") || name.contains("genre")
+}
+
+/// Normalises a category name as written in wikitext (i.e. `Category:Brazilian_styles of music`).
+pub fn normalise_category(name: &str) -> Option<String> {
+    let name = name.trim();
+    let (namespace, name) = name.split_once(':')?;
+    if !namespace.trim().eq_ignore_ascii_case("category") {
+        return None;
+    }
+    let name = name.trim().replace('_', " ");
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    Some(first.to_uppercase().chain(chars).collect())
+}
+
+/// The categories in a page's wikitext. This is a textual scan, as parsing every category
+/// page in the dump would be slow.
+pub fn from_wikitext(wikitext: &str) -> Vec<String> {
+    let mut parents = vec![];
+    for rest in wikitext.split("[[").skip(1) {
+        let Some(end) = rest.find(['|', ']']) else {
+            continue;
+        };
+        if let Some(parent) = normalise_category(&rest[..end]) {
+            if !parents.contains(&parent) {
+                parents.push(parent);
+            }
+        }
+    }
+    parents
+}
+
+/// The categories of a page, from its top-level nodes.
+pub fn from_nodes(nodes: &[pwt::Node]) -> Vec<String> {
+    let mut categories = vec![];
+    for node in nodes {
+        if let pwt::Node::Category { target, .. } = node {
+            if let Some(category) = normalise_category(target) {
+                if !categories.contains(&category) {
+                    categories.push(category);
+                }
+            }
+        }
+    }
+    categories
+}
+
+/// Derives tags for every genre and stub by walking up the category tree from its categories
+/// to `roots`.
+///
+/// A root named as a grouping (i.e. "Music genres by country") tags a genre with the child
+/// of the root that it was reached through ("Music genres of Brazil"); any other root
+/// (i.e. "Electronic music genres") is used as the tag itself.
+pub fn tag_genres(
+    start: std::time::Instant,
+    category_parents: &CategoryParents,
+    roots: &[String],
+    processed_genres: &ProcessedGenres,
+    stubs: &Stubs,
+) -> CategoryTags {
+    let roots = roots.iter().map(|r| r.as_str()).collect::<HashSet<_>>();
+    let tags = CategoryTags(
+        processed_genres
+            .0
+            .iter()
+            .map(|(page, pg)| (page, &pg.categories))
+            .chain(
+                stubs
+                    .stubs
+                    .iter()
+                    .map(|stub| (&stub.page, &stub.categories)),
+            )
+            .map(|(page, categories)| {
+                (
+                    page.clone(),
+                    tags_for_categories(category_parents, &roots, categories),
+                )
+            })
+            .filter(|(_, tags)| !tags.is_empty())
+            .collect(),
+    );
+    println!(
+        "{:.2}s: tagged {} genres and stubs from their categories",
+        start.elapsed().as_secs_f32(),
+        tags.0.len()
+    );
+    tags
+}
+
+fn tags_for_categories(
+    category_parents: &CategoryParents,
+    roots: &HashSet<&str>,
+    categories: &[String],
+) -> BTreeSet<String> {
+    let is_grouping = |root: &str| root.contains(" by ");
+
+    let mut tags = BTreeSet::new();
+    let mut visited = HashSet::new();
+    let mut frontier = categories.iter().map(|c| c.as_str()).collect::<Vec<_>>();
+    for _ in 0..=MAX_DEPTH {
+        let mut next = vec![];
+        for category in frontier {
+            if !visited.insert(category) {
+                continue;
+            }
+            if roots.contains(category) {
+                if !is_grouping(category) {
+                    tags.insert(category.to_string());
+                }
+                // Don't walk above a root
+                continue;
+            }
+            for parent in category_parents.0.get(category).into_iter().flatten() {
+                if roots.contains(parent.as_str()) && is_grouping(parent) {
+                    tags.insert(category.to_string());
+                }
+                next.push(parent.as_str());
+            }
+        }
+        frontier = next;
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_wikitext() {
+        assert_eq!(
+            from_wikitext(
+                "{{Commons category}}\n[[Category:Music genres by country| Brazil]]\n[[category:Brazilian_music]]\n[[Samba]]"
+            ),
+            vec!["Music genres by country", "Brazilian music"]
+        );
+    }
+
+    #[test]
+    fn test_tags() {
+        let category_parents = CategoryParents(HashMap::from_iter(
+            [
+                ("Brazilian styles of music", vec!["Music genres of Brazil"]),
+                ("Music genres of Brazil", vec!["Music genres by country"]),
+                ("Music genres by country", vec!["Music genres"]),
+                (
+                    "Electronic dance music genres",
+                    vec!["Electronic music genres"],
+                ),
+                ("Electronic music genres", vec!["Music genres"]),
+            ]
+            .map(|(category, parents)| {
+                (
+                    category.to_string(),
+                    parents.into_iter().map(String::from).collect(),
+                )
+            }),
+        ));
+        let roots = HashSet::from_iter(["Music genres by country", "Electronic music genres"]);
+        assert_eq!(
+            tags_for_categories(
+                &category_parents,
+                &roots,
+                &[
+                    "Brazilian styles of music".to_string(),
+                    "Electronic dance music genres".to_string()
+                ]
+            ),
+            BTreeSet::from_iter([
+                "Electronic music genres".to_string(),
+                "Music genres of Brazil".to_string()
+            ])
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, HashMap};
+
+use parse_wiki_text_2 as pwt;
+use serde::{Deserialize, Serialize};
+use wikitext_util::{nodes_inner_text, InnerTextConfig};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+/// A citation from a `<ref>` tag.
+///
+/// If the ref contains a citation template (`{{cite web}}`, `{{cite book}}`, ...), its fields
+/// are extracted; otherwise, the ref's content is kept as wikitext.
+pub struct Citation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The citation template used, lower-cased (i.e. `cite web`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wikitext: Option<String>,
+}
+impl Citation {
+    /// Builds a citation from the wikitext at `start` (the start of a `<ref>` node), resolving
+    /// reused named refs through `named_refs`.
+    pub fn from_ref_at(
+        pwt_configuration: &pwt::Configuration,
+        wikitext: &str,
+        start: usize,
+        named_refs: &HashMap<String, String>,
+    ) -> Option<Self> {
+        let (name, content) = parse_ref(ref_at(wikitext, start)?);
+        let content = content.or_else(|| named_refs.get(name.as_deref()?).cloned());
+        if name.is_none() && content.is_none() {
+            return None;
+        }
+
+        let mut citation = Citation {
+            name,
+            ..Default::default()
+        };
+        let Some(content) = content else {
+            return Some(citation);
+        };
+
+        let parsed = pwt_configuration
+            .parse_with_timeout(&content, std::time::Duration::from_secs(1))
+            .ok();
+        let template = parsed.as_ref().and_then(|parsed| {
+            parsed.nodes.iter().find_map(|node| match node {
+                pwt::Node::Template {
+                    name, parameters, ..
+                } => {
+                    let name = nodes_inner_text(name, &InnerTextConfig::default()).to_lowercase();
+                    (name.starts_with("cite") || name == "citation").then_some((name, parameters))
+                }
+                _ => None,
+            })
+        });
+        match template {
+            Some((template_name, parameters)) => {
+                citation.template = Some(template_name);
+                citation.fields = parameters
+                    .iter()
+                    .filter_map(|p| {
+                        let name =
+                            nodes_inner_text(p.name.as_deref()?, &InnerTextConfig::default())
+                                .to_lowercase();
+                        let value = nodes_inner_text(&p.value, &InnerTextConfig::default());
+                        (!value.is_empty()).then_some((name, value))
+                    })
+                    .collect();
+            }
+            None => citation.wikitext = Some(content),
+        }
+        Some(citation)
+    }
+
+    fn same_source(&self, other: &Citation) -> bool {
+        match (&self.name, &other.name) {
+            (Some(a), Some(b)) if a == b => true,
+            _ => {
+                (self.template.is_some() || self.wikitext.is_some())
+                    && self.template == other.template
+                    && self.fields == other.fields
+                    && self.wikitext == other.wikitext
+            }
+        }
+    }
+}
+
+/// Adds `citation` to `references` unless it's already present (by name or content),
+/// returning its index in the list.
+pub fn add_reference(references: &mut Vec<Citation>, citation: Citation) -> usize {
+    match references.iter().position(|r| r.same_source(&citation)) {
+        Some(index) => {
+            // Fill in the name if this is the first time we've seen it for this source
+            if references[index].name.is_none() {
+                references[index].name = citation.name;
+            }
+            index
+        }
+        None => {
+            references.push(citation);
+            references.len() - 1
+        }
+    }
+}
+
+/// Collects the content of every named ref defined in the article, so that reused refs
+/// (`<ref name="x" />`) can be resolved regardless of where they're defined.
+pub fn collect_named_refs(wikitext: &str) -> HashMap<String, String> {
+    let mut named_refs = HashMap::new();
+    let mut offset = 0;
+    while let Some(index) = wikitext[offset..].find("<ref") {
+        let start = offset + index;
+        let Some(raw) = ref_at(wikitext, start) else {
+            offset = start + 4;
+            continue;
+        };
+        if let (Some(name), Some(content)) = parse_ref(raw) {
+            named_refs.entry(name).or_insert(content);
+        }
+        offset = start + raw.len();
+    }
+    named_refs
+}
+
+/// Counts the distinct refs in the article; reused refs (`<ref name="x" />`) aren't counted
+/// again.
+pub fn count_refs(wikitext: &str) -> usize {
+    let mut count = 0;
+    let mut offset = 0;
+    while let Some(index) = wikitext[offset..].find("<ref") {
+        let start = offset + index;
+        let Some(raw) = ref_at(wikitext, start) else {
+            offset = start + 4;
+            continue;
+        };
+        if parse_ref(raw).1.is_some() {
+            count += 1;
+        }
+        offset = start + raw.len();
+    }
+    count
+}
+
+/// Returns the wikitext of the ref starting at `start`, including its opening and closing tags.
+fn ref_at(wikitext: &str, start: usize) -> Option<&str> {
+    let rest = wikitext.get(start..)?.strip_prefix("<ref")?;
+    // Make sure this isn't `<references>` or similar
+    if !rest.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+        return None;
+    }
+    let opening_end = rest.find('>')? + 1;
+    if rest[..opening_end].ends_with("/>") {
+        return Some(&wikitext[start..start + 4 + opening_end]);
+    }
+    let end = rest
+        .find("</ref>")
+        .map(|i| i + "</ref>".len())
+        .unwrap_or(rest.len());
+    Some(&wikitext[start..start + 4 + end])
+}
+
+/// Splits a ref into its name and content, either of which may be absent.
+fn parse_ref(raw: &str) -> (Option<String>, Option<String>) {
+    let Some(opening_end) = raw.find('>') else {
+        return (None, None);
+    };
+    let opening = &raw[..opening_end];
+    let name = attribute_value(opening.trim_start_matches("<ref"), "name");
+    if opening.ends_with('/') {
+        return (name, None);
+    }
+
+    let rest = &raw[opening_end + 1..];
+    let content = rest[..rest.find("</ref>").unwrap_or(rest.len())].trim();
+    (name, (!content.is_empty()).then(|| content.to_string()))
+}
+
+fn attribute_value(attributes: &str, key: &str) -> Option<String> {
+    let mut offset = 0;
+    while let Some(index) = attributes[offset..].find(key) {
+        let index = offset + index;
+        offset = index + key.len();
+        if !attributes[..index]
+            .chars()
+            .last()
+            .is_none_or(char::is_whitespace)
+        {
+            continue;
+        }
+        let Some(value) = attributes[offset..].trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next(),
+            _ => value.split(|c: char| c.is_whitespace() || c == '/').next(),
+        };
+        return value
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wikitext_util::pwt_configuration;
+
+    #[test]
+    fn test_parse_ref() {
+        assert_eq!(
+            parse_ref(r#"<ref name="allmusic">Some text</ref>"#),
+            (Some("allmusic".to_string()), Some("Some text".to_string()))
+        );
+        assert_eq!(
+            parse_ref("<ref name=allmusic />"),
+            (Some("allmusic".to_string()), None)
+        );
+        assert_eq!(
+            parse_ref("<ref>Some text</ref>"),
+            (None, Some("Some text".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_collect_named_refs() {
+        let wikitext = r#"Intro.<ref name="a">First</ref> More.<ref name="a"/> Then.<ref name='b'>Second</ref>
+==References==
+<references />"#;
+        let named_refs = collect_named_refs(wikitext);
+        assert_eq!(named_refs.len(), 2);
+        assert_eq!(named_refs["a"], "First");
+        assert_eq!(named_refs["b"], "Second");
+    }
+
+    #[test]
+    fn test_citation_from_reused_ref() {
+        let wikitext = r#"[[Disco]]<ref name="am" />, [[Funk]]
+Body text.<ref name="am">{{cite web|url=https://www.allmusic.com/style/house|title=House|website=[[AllMusic]]}}</ref>"#;
+        let named_refs = collect_named_refs(wikitext);
+        let start = wikitext.find("<ref").unwrap();
+        let citation =
+            Citation::from_ref_at(&pwt_configuration(), wikitext, start, &named_refs).unwrap();
+        assert_eq!(
+            citation,
+            Citation {
+                name: Some("am".to_string()),
+                template: Some("cite web".to_string()),
+                fields: BTreeMap::from([
+                    (
+                        "url".to_string(),
+                        "https://www.allmusic.com/style/house".to_string()
+                    ),
+                    ("title".to_string(), "House".to_string()),
+                    ("website".to_string(), "AllMusic".to_string()),
+                ]),
+                wikitext: None,
+            }
+        );
+
+        let mut references = vec![];
+        assert_eq!(add_reference(&mut references, citation.clone()), 0);
+        assert_eq!(
+            add_reference(
+                &mut references,
+                Citation {
+                    name: None,
+                    ..citation
+                }
+            ),
+            0
+        );
+    }
+}
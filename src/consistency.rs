@@ -0,0 +1,345 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    path::Path,
+};
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::{DumpMeta, LinksToArticles, PageName, ProcessedGenres};
+
+#[derive(Copy, Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+/// How well the infoboxes of two genres agree about their relationship.
+///
+/// Ordered by how urgently the relationship needs an editor's attention.
+pub enum Status {
+    /// The infoboxes describe the relationship in conflicting ways (i.e. in both directions,
+    /// or as both a subgenre and a derivative)
+    Contradictory,
+    /// Only one of the infoboxes describes the relationship
+    OneSided,
+    /// Both infoboxes describe the relationship in the same direction
+    Reciprocal,
+}
+impl Status {
+    fn heading(&self) -> &'static str {
+        match self {
+            Status::Contradictory => "Contradictory",
+            Status::OneSided => "One-sided",
+            Status::Reciprocal => "Reciprocal",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+/// A claim made by one page's infobox about its relationship with another genre.
+pub struct Claim {
+    /// The page whose infobox makes the claim
+    pub page: PageName,
+    /// The infobox parameter (i.e. `stylistic_origins`)
+    pub field: String,
+    pub other: PageName,
+}
+impl Claim {
+    /// The direction of the claimed relationship, as `(origin, descendant)`.
+    ///
+    /// `stylistic_origins` points backwards; every other field points forwards.
+    fn direction(&self) -> (&PageName, &PageName) {
+        if self.field == "stylistic_origins" {
+            (&self.other, &self.page)
+        } else {
+            (&self.page, &self.other)
+        }
+    }
+
+    /// The claim the other page would have to make to agree with this one.
+    fn reciprocal(&self) -> Claim {
+        Claim {
+            page: self.other.clone(),
+            field: if self.field == "stylistic_origins" {
+                "derivatives"
+            } else {
+                "stylistic_origins"
+            }
+            .to_string(),
+            other: self.page.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+/// Every claim made about the relationship between two genres, and how well they agree.
+pub struct Relationship {
+    pub status: Status,
+    /// The two genres, in sorted order
+    pub genres: [PageName; 2],
+    pub claims: Vec<Claim>,
+    /// The claims that would make a one-sided relationship reciprocal
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<Claim>,
+}
+
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    wikipedia_domain: &'a str,
+    dump_date: jiff::civil::Date,
+    counts: BTreeMap<Status, usize>,
+    relationships: &'a [Relationship],
+}
+
+/// Analyses every infobox relationship between the processed genres, and writes the results
+/// to `consistency.json` and `consistency.md` in `reports_path`.
+///
+/// The relationships are returned so that fixes can be suggested for them.
+pub fn produce_report(
+    start: std::time::Instant,
+    dump_meta: &DumpMeta,
+    reports_path: &Path,
+    links_to_articles: &LinksToArticles,
+    processed_genres: &ProcessedGenres,
+) -> anyhow::Result<Vec<Relationship>> {
+    let relationships = analyse(links_to_articles, processed_genres);
+    let mut counts: BTreeMap<Status, usize> = BTreeMap::new();
+    for relationship in &relationships {
+        *counts.entry(relationship.status).or_default() += 1;
+    }
+
+    std::fs::create_dir_all(reports_path)?;
+    std::fs::write(
+        reports_path.join("consistency.json"),
+        serde_json::to_string_pretty(&Report {
+            wikipedia_domain: &dump_meta.wikipedia_domain,
+            dump_date: dump_meta.dump_date,
+            counts: counts.clone(),
+            relationships: &relationships,
+        })?,
+    )
+    .context("Failed to write consistency report")?;
+    std::fs::write(
+        reports_path.join("consistency.md"),
+        to_markdown(dump_meta, &counts, &relationships),
+    )
+    .context("Failed to write consistency report")?;
+
+    println!(
+        "{:.2}s: wrote consistency report ({} relationships: {})",
+        start.elapsed().as_secs_f32(),
+        relationships.len(),
+        counts
+            .iter()
+            .map(|(status, count)| format!("{count} {}", status.heading().to_lowercase()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    Ok(relationships)
+}
+
+/// Gathers the claims made by every infobox about each pair of genres and classifies them,
+/// sorted so that the relationships most in need of attention come first.
+pub fn analyse(
+    links_to_articles: &LinksToArticles,
+    processed_genres: &ProcessedGenres,
+) -> Vec<Relationship> {
+    let mut claims_by_pair: BTreeMap<[PageName; 2], Vec<Claim>> = BTreeMap::new();
+    for (page, processed_genre) in &processed_genres.0 {
+        for (field, links) in [
+            ("stylistic_origins", &processed_genre.stylistic_origins),
+            ("derivatives", &processed_genre.derivatives),
+            ("subgenres", &processed_genre.subgenres),
+            ("fusiongenres", &processed_genre.fusion_genres),
+        ] {
+            for link in links {
+                let Some(other) = links_to_articles
+                    .map(link)
+                    .filter(|other| other != page && processed_genres.0.contains_key(other))
+                else {
+                    continue;
+                };
+                let mut pair = [page.clone(), other.clone()];
+                pair.sort();
+                claims_by_pair.entry(pair).or_default().push(Claim {
+                    page: page.clone(),
+                    field: field.to_string(),
+                    other,
+                });
+            }
+        }
+    }
+
+    let mut relationships = claims_by_pair
+        .into_iter()
+        .map(|(genres, mut claims)| {
+            claims.sort();
+            claims.dedup();
+            let status = classify(&claims);
+            let missing = match status {
+                Status::OneSided => {
+                    let mut missing = claims.iter().map(Claim::reciprocal).collect::<Vec<_>>();
+                    missing.sort();
+                    missing.dedup();
+                    missing
+                }
+                _ => vec![],
+            };
+            Relationship {
+                status,
+                genres,
+                claims,
+                missing,
+            }
+        })
+        .collect::<Vec<_>>();
+    // Pairs are already in order, and the sort is stable
+    relationships.sort_by_key(|r| r.status);
+    relationships
+}
+
+/// Classifies the claims made about a single pair of genres.
+///
+/// A `stylistic_origins` entry agrees with any forward claim (`derivatives`, `subgenres`
+/// or `fusiongenres`) from the other page, as the infobox has no more specific field for it.
+pub fn classify(claims: &[Claim]) -> Status {
+    let directions = claims.iter().map(Claim::direction).collect::<BTreeSet<_>>();
+    let mut forward_fields: BTreeMap<&PageName, BTreeSet<&str>> = BTreeMap::new();
+    for claim in claims.iter().filter(|c| c.field != "stylistic_origins") {
+        forward_fields
+            .entry(&claim.page)
+            .or_default()
+            .insert(&claim.field);
+    }
+
+    if directions.len() > 1 || forward_fields.values().any(|fields| fields.len() > 1) {
+        Status::Contradictory
+    } else if claims
+        .iter()
+        .map(|c| &c.page)
+        .collect::<BTreeSet<_>>()
+        .len()
+        > 1
+    {
+        Status::Reciprocal
+    } else {
+        Status::OneSided
+    }
+}
+
+fn to_markdown(
+    dump_meta: &DumpMeta,
+    counts: &BTreeMap<Status, usize>,
+    relationships: &[Relationship],
+) -> String {
+    let page_link = |page: &PageName| {
+        format!(
+            "[{page}](https://{}/wiki/{})",
+            dump_meta.wikipedia_domain,
+            page.linksafe()
+        )
+    };
+
+    let mut output = String::new();
+    writeln!(output, "# Infobox consistency report").unwrap();
+    writeln!(output).unwrap();
+    writeln!(
+        output,
+        "Generated from the `{}` dump of {}. Reciprocal relationships are only included in `consistency.json`.",
+        dump_meta.wikipedia_db_name, dump_meta.dump_date
+    )
+    .unwrap();
+    writeln!(output).unwrap();
+    writeln!(output, "| Status | Relationships |").unwrap();
+    writeln!(output, "| --- | --- |").unwrap();
+    for (status, count) in counts {
+        writeln!(output, "| {} | {count} |", status.heading()).unwrap();
+    }
+
+    for status in [Status::Contradictory, Status::OneSided] {
+        writeln!(output).unwrap();
+        writeln!(output, "## {}", status.heading()).unwrap();
+        writeln!(output).unwrap();
+        writeln!(output, "| Genre | Genre | Claims | Missing |").unwrap();
+        writeln!(output, "| --- | --- | --- | --- |").unwrap();
+        for relationship in relationships.iter().filter(|r| r.status == status) {
+            let describe = |claims: &[Claim]| {
+                claims
+                    .iter()
+                    .map(|c| format!("{} lists {} in `{}`", c.page, c.other, c.field))
+                    .collect::<Vec<_>>()
+                    .join("<br>")
+            };
+            writeln!(
+                output,
+                "| {} | {} | {} | {} |",
+                page_link(&relationship.genres[0]),
+                page_link(&relationship.genres[1]),
+                describe(&relationship.claims),
+                describe(&relationship.missing),
+            )
+            .unwrap();
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(page: &str, field: &str, other: &str) -> Claim {
+        Claim {
+            page: PageName::new(page, None),
+            field: field.to_string(),
+            other: PageName::new(other, None),
+        }
+    }
+
+    #[test]
+    fn test_reciprocal() {
+        assert_eq!(
+            classify(&[
+                claim("Funk", "derivatives", "Go-go"),
+                claim("Go-go", "stylistic_origins", "Funk"),
+            ]),
+            Status::Reciprocal
+        );
+        assert_eq!(
+            classify(&[
+                claim("House music", "subgenres", "Acid house"),
+                claim("Acid house", "stylistic_origins", "House music"),
+            ]),
+            Status::Reciprocal
+        );
+    }
+
+    #[test]
+    fn test_one_sided() {
+        let claims = [claim("Funk", "derivatives", "Go-go")];
+        assert_eq!(classify(&claims), Status::OneSided);
+        assert_eq!(
+            claims[0].reciprocal(),
+            claim("Go-go", "stylistic_origins", "Funk")
+        );
+    }
+
+    #[test]
+    fn test_contradictory() {
+        // Both directions
+        assert_eq!(
+            classify(&[
+                claim("Funk", "derivatives", "Go-go"),
+                claim("Funk", "stylistic_origins", "Go-go"),
+            ]),
+            Status::Contradictory
+        );
+        // Both a subgenre and a derivative
+        assert_eq!(
+            classify(&[
+                claim("Techno", "derivatives", "Acid techno"),
+                claim("Techno", "subgenres", "Acid techno"),
+            ]),
+            Status::Contradictory
+        );
+    }
+}
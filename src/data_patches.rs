@@ -2,7 +2,7 @@ use std::{collections::HashMap, str::FromStr};
 
 use jiff::Timestamp;
 
-use crate::{GenreName, PageName};
+use crate::{advisories::Advisory, node_kinds::NodeKind, GenreName, PageName};
 
 pub fn all() -> HashMap<PageName, (Option<Timestamp>, GenreName)> {
     fixed_already().into_iter().chain(unclear_fixes()).collect()
@@ -18,9 +18,35 @@ pub fn pages_to_ignore() -> Vec<PageName> {
     .collect()
 }
 
+/// Overrides for pages that [`crate::node_kinds::classify`] gets wrong.
+pub fn node_kinds() -> HashMap<PageName, NodeKind> {
+    [
+        // An avant-garde movement; the title looks like "<genre> in <place>"
+        ("Rock in Opposition", NodeKind::Movement),
+        // Music associated with the LGBTQ rights movement, rather than a style
+        ("LGBTQ music", NodeKind::Movement),
+    ]
+    .into_iter()
+    .map(|(page, kind)| (PageName::new(page, None), kind))
+    .collect()
+}
+
+/// Overrides for the advisories derived by [`crate::advisories::from_categories`]; `None`
+/// removes an advisory from a page.
+pub fn advisories() -> HashMap<PageName, Option<Advisory>> {
+    [
+        ("Nazi punk", Some(Advisory::HateMusic)),
+        ("National Socialist black metal", Some(Advisory::HateMusic)),
+        ("Rock Against Communism", Some(Advisory::HateMusic)),
+    ]
+    .into_iter()
+    .map(|(page, advisory)| (PageName::new(page, None), advisory))
+    .collect()
+}
+
 /// Patches that have already been applied to Wikipedia, but may not be
 /// in the dump being processed.
-fn fixed_already() -> HashMap<PageName, (Option<Timestamp>, GenreName)> {
+pub fn fixed_already() -> HashMap<PageName, (Option<Timestamp>, GenreName)> {
     [
         // The infobox for the page 'Hip-hop in the Pacific Northwest' has a = instead of a - in the name.
         (
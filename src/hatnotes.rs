@@ -0,0 +1,113 @@
+use parse_wiki_text_2 as pwt;
+use serde::{Deserialize, Serialize};
+use wikitext_util::{nodes_inner_text, InnerTextConfig};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+/// The kind of relationship described by a hatnote.
+pub enum RelationType {
+    /// `{{Distinguish}}`, as well as the other uses listed by `{{About}}`, `{{Redirect}}` and `{{For}}`
+    ConfusedWith,
+    /// `{{See also}}` and `{{Further}}`
+    SeeAlso,
+    /// `{{Main}}`
+    MainArticle,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// A relationship to another page from a hatnote. The target is an unresolved link.
+pub struct Hatnote {
+    pub ty: RelationType,
+    pub target: String,
+}
+
+/// Extracts the hatnotes described by a template, if it is a hatnote template.
+pub fn from_template(template_name: &str, parameters: &[pwt::Parameter]) -> Vec<Hatnote> {
+    let positional = parameters
+        .iter()
+        .filter(|p| p.name.is_none())
+        .map(|p| nodes_inner_text(&p.value, &InnerTextConfig::default()))
+        .collect::<Vec<_>>();
+
+    /// `{{About}}` and `{{Redirect}}` alternate between a description of the other use
+    /// and the page for it, with `and` introducing another page for the previous use,
+    /// so we can take every second value.
+    fn paired_targets(values: &[String]) -> Vec<String> {
+        values
+            .chunks(2)
+            .filter_map(|chunk| chunk.get(1).cloned())
+            .collect()
+    }
+
+    let (ty, targets) = match template_name {
+        "distinguish" => (RelationType::ConfusedWith, positional),
+        "about" | "redirect" => (
+            RelationType::ConfusedWith,
+            paired_targets(positional.get(1..).unwrap_or_default()),
+        ),
+        "for" => (
+            RelationType::ConfusedWith,
+            positional.get(1..).unwrap_or_default().to_vec(),
+        ),
+        "other uses" => (RelationType::ConfusedWith, positional),
+        "main" | "main article" => (RelationType::MainArticle, positional),
+        "see also" | "further" => (RelationType::SeeAlso, positional),
+        _ => return vec![],
+    };
+
+    targets
+        .into_iter()
+        .map(|target| target.trim().to_string())
+        .filter(|target| !target.is_empty() && target != "and")
+        .map(|target| Hatnote { ty, target })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hatnotes(wikitext: &str) -> Vec<(RelationType, String)> {
+        let mut output = vec![];
+        crate::for_each_template(wikitext, |template_name, parameters| {
+            output.extend(
+                from_template(template_name, parameters)
+                    .into_iter()
+                    .map(|h| (h.ty, h.target)),
+            )
+        });
+        output
+    }
+
+    #[test]
+    fn test_distinguish_and_main() {
+        assert_eq!(
+            hatnotes(
+                "{{Distinguish|Grindcore|text=[[Grind (music)]]}}\n{{Main|History of techno}}"
+            ),
+            vec![
+                (RelationType::ConfusedWith, "Grindcore".to_string()),
+                (RelationType::MainArticle, "History of techno".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_about_and_redirect() {
+        assert_eq!(
+            hatnotes(
+                "{{About|the music genre|the dance|Breakdance|the film|Breakin'|and|Breakin' 2}}\n{{Redirect|DnB|the band|DnB (band)}}"
+            ),
+            vec![
+                (RelationType::ConfusedWith, "Breakdance".to_string()),
+                (RelationType::ConfusedWith, "Breakin'".to_string()),
+                (RelationType::ConfusedWith, "Breakin' 2".to_string()),
+                (RelationType::ConfusedWith, "DnB (band)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_not_a_hatnote() {
+        assert_eq!(hatnotes("{{Use dmy dates|date=January 2025}}"), vec![]);
+    }
+}
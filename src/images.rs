@@ -0,0 +1,195 @@
+use parse_wiki_text_2 as pwt;
+use serde::{Deserialize, Serialize};
+use wikitext_util::{nodes_inner_text, nodes_inner_wikitext, InnerTextConfig, NodeMetadata};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// The image that leads a genre's article or section, from the infobox or the lead.
+pub struct LeadImage {
+    /// The file name without the `File:` prefix; the frontend is responsible for building a URL.
+    pub file_name: String,
+    /// Wikitext, as captions often contain links
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+}
+
+/// Extracts the lead image from the `image`, `caption` and `alt` parameters of an infobox.
+///
+/// The `image` parameter can either be a bare file name or a full `[[File:...]]` link; in the
+/// case of the latter, the explicit `caption` and `alt` parameters take precedence.
+pub fn from_infobox(
+    wikitext: &str,
+    image: &[pwt::Node],
+    caption: Option<&[pwt::Node]>,
+    alt: Option<&[pwt::Node]>,
+) -> Option<LeadImage> {
+    let mut lead_image = match image.iter().find(|n| matches!(n, pwt::Node::Image { .. })) {
+        Some(node) => {
+            let metadata = NodeMetadata::for_node(node);
+            from_image_wikitext(&wikitext[metadata.start..metadata.end])?
+        }
+        None => LeadImage {
+            file_name: strip_file_prefix(&nodes_inner_text(image, &InnerTextConfig::default()))?,
+            caption: None,
+            alt: None,
+        },
+    };
+
+    if let Some(caption) = caption
+        .map(|nodes| nodes_inner_wikitext(wikitext, nodes).trim().to_string())
+        .filter(|s| !s.is_empty())
+    {
+        lead_image.caption = Some(caption);
+    }
+    if let Some(alt) = alt
+        .map(|nodes| nodes_inner_text(nodes, &InnerTextConfig::default()))
+        .filter(|s| !s.is_empty())
+    {
+        lead_image.alt = Some(alt);
+    }
+
+    Some(lead_image)
+}
+
+/// Extracts an image from the wikitext of an image link (i.e. `[[File:X.jpg|thumb|alt=Y|Z]]`).
+///
+/// The caption is the last parameter that isn't a formatting option.
+pub fn from_image_wikitext(image_wikitext: &str) -> Option<LeadImage> {
+    let inner = image_wikitext.strip_prefix("[[")?.strip_suffix("]]")?;
+    let mut parts = split_top_level_pipes(inner).into_iter();
+    let file_name = strip_file_prefix(parts.next()?)?;
+
+    let mut caption = None;
+    let mut alt = None;
+    for part in parts.map(str::trim) {
+        if let Some(new_alt) = part.strip_prefix("alt=") {
+            alt = Some(new_alt.trim().to_string()).filter(|s| !s.is_empty());
+        } else if !part.is_empty() && !is_image_option(part) {
+            caption = Some(part.to_string());
+        }
+    }
+
+    Some(LeadImage {
+        file_name,
+        caption,
+        alt,
+    })
+}
+
+fn strip_file_prefix(name: &str) -> Option<String> {
+    let name = name.trim();
+    let name = match name.split_once(':') {
+        Some((namespace, name))
+            if ["file", "image"].contains(&namespace.trim().to_lowercase().as_str()) =>
+        {
+            name.trim()
+        }
+        _ => name,
+    };
+    (!name.is_empty()).then(|| name.replace('_', " "))
+}
+
+/// Splits on `|`, ignoring any that are within links or templates.
+fn split_top_level_pipes(wikitext: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut part_start = 0;
+    let mut i = 0;
+    let bytes = wikitext.as_bytes();
+    while i < bytes.len() {
+        match &bytes[i..] {
+            [b'[', b'[', ..] | [b'{', b'{', ..] => {
+                depth += 1;
+                i += 2;
+            }
+            [b']', b']', ..] | [b'}', b'}', ..] => {
+                depth = depth.saturating_sub(1);
+                i += 2;
+            }
+            [b'|', ..] if depth == 0 => {
+                parts.push(&wikitext[part_start..i]);
+                i += 1;
+                part_start = i;
+            }
+            _ => i += 1,
+        }
+    }
+    parts.push(&wikitext[part_start..]);
+    parts
+}
+
+fn is_image_option(part: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "thumb",
+        "thumbnail",
+        "frame",
+        "framed",
+        "frameless",
+        "border",
+        "left",
+        "right",
+        "center",
+        "centre",
+        "none",
+        "upright",
+        "baseline",
+        "middle",
+        "sub",
+        "super",
+        "text-top",
+        "text-bottom",
+        "top",
+        "bottom",
+    ];
+    const KEYED_OPTIONS: &[&str] = &[
+        "upright",
+        "link",
+        "lang",
+        "page",
+        "class",
+        "thumb",
+        "thumbnail",
+        "alt",
+    ];
+
+    let part = part.to_lowercase();
+    KEYWORDS.contains(&part.as_str())
+        || part.strip_suffix("px").is_some_and(|size| {
+            !size.is_empty() && size.chars().all(|c| c.is_ascii_digit() || c == 'x')
+        })
+        || part
+            .split_once('=')
+            .is_some_and(|(key, _)| KEYED_OPTIONS.contains(&key.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_wikitext() {
+        assert_eq!(
+            from_image_wikitext(
+                "[[File:Kraftwerk_live.jpg|thumb|upright=1.2|250px|alt=Four men on a stage|[[Kraftwerk]] performing in 2004]]"
+            ),
+            Some(LeadImage {
+                file_name: "Kraftwerk live.jpg".to_string(),
+                caption: Some("[[Kraftwerk]] performing in 2004".to_string()),
+                alt: Some("Four men on a stage".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_image_wikitext_without_caption() {
+        assert_eq!(
+            from_image_wikitext("[[Image:Turntables.jpg|frameless]]"),
+            Some(LeadImage {
+                file_name: "Turntables.jpg".to_string(),
+                caption: None,
+                alt: None,
+            })
+        );
+    }
+}
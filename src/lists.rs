@@ -0,0 +1,293 @@
+use anyhow::Context;
+use parse_wiki_text_2 as pwt;
+use serde::{Deserialize, Serialize};
+use wikitext_util::{nodes_inner_text, pwt_configuration, InnerTextConfig};
+
+use crate::{get_links_from_nodes, GenrePages, PageName, WikitextHeader};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// A genre listed in a list or table, either in a genre's "Subgenres" or "Styles" section,
+/// or in a list article. Links are unresolved.
+pub struct ListedGenre {
+    /// The parent genre, if the list specifies one (i.e. through nesting, or a "Parent" column);
+    /// otherwise, the parent is implied by where the list is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    pub link: String,
+    /// The heading of the section the list is in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+/// The genres listed by an article like "List of electronic music genres".
+pub struct ListArticle {
+    pub page: PageName,
+    pub genres: Vec<ListedGenre>,
+}
+
+/// Whether a section of a genre's article is likely to list its subgenres.
+///
+/// "Styles" is only a subgenre heading on its own, as "Styles of ..." or as the last word of
+/// the heading (i.e. "Regional styles"), as headings like "Lifestyles" or "Dance styles"
+/// aren't about the genre's music.
+pub fn is_subgenre_heading(heading: &str) -> bool {
+    let heading = heading.trim().to_lowercase();
+    if ["subgenre", "sub-genre", "substyle"]
+        .iter()
+        .any(|keyword| heading.contains(keyword))
+    {
+        return true;
+    }
+    let mut words = heading.split_whitespace().rev();
+    match (words.next(), words.next()) {
+        (Some("styles"), None) => true,
+        (Some("styles"), Some(previous)) => !["dance", "fashion", "clothing"].contains(&previous),
+        _ => heading.starts_with("styles of "),
+    }
+}
+
+/// Whether a list article's title suggests that it lists genres.
+pub fn is_list_article(title: &str) -> bool {
+    title.starts_with("List of") && (title.contains("genres") || title.contains("styles"))
+}
+
+/// Parses every list article, ignoring any that fail to parse.
+pub fn process_list_articles(
+    start: std::time::Instant,
+    list_article_pages: &GenrePages,
+) -> anyhow::Result<Vec<ListArticle>> {
+    let pwt_configuration = pwt_configuration();
+    let mut list_articles = vec![];
+    for (page, path) in list_article_pages.iter() {
+        let wikitext = std::fs::read_to_string(path)?;
+        let (wikitext_header, wikitext) = wikitext
+            .split_once('\n')
+            .with_context(|| format!("Missing header for {page}"))?;
+        let _: WikitextHeader = serde_json::from_str(wikitext_header)?;
+        let Ok(parsed_wikitext) =
+            pwt_configuration.parse_with_timeout(wikitext, std::time::Duration::from_secs(5))
+        else {
+            eprintln!("Failed to parse list article {page}");
+            continue;
+        };
+        list_articles.push(ListArticle {
+            page: page.clone(),
+            genres: from_list_article(&parsed_wikitext.nodes),
+        });
+    }
+    list_articles.sort_by(|a, b| a.page.cmp(&b.page));
+
+    println!(
+        "{:.2}s: processed {} list articles ({} listed genres)",
+        start.elapsed().as_secs_f32(),
+        list_articles.len(),
+        list_articles
+            .iter()
+            .map(|la| la.genres.len())
+            .sum::<usize>()
+    );
+    Ok(list_articles)
+}
+
+/// Extracts every listed genre from the top-level nodes of a list article, skipping the
+/// sections at the end of the article that list other things.
+fn from_list_article(nodes: &[pwt::Node]) -> Vec<ListedGenre> {
+    const IGNORED_SECTIONS: &[&str] = &[
+        "see also",
+        "notes",
+        "references",
+        "further reading",
+        "external links",
+        "bibliography",
+    ];
+
+    let mut section = None;
+    let mut output = vec![];
+    for node in nodes {
+        if let pwt::Node::Heading { nodes, .. } = node {
+            section = Some(nodes_inner_text(nodes, &InnerTextConfig::default()));
+            continue;
+        }
+        if section
+            .as_deref()
+            .is_some_and(|s| IGNORED_SECTIONS.contains(&s.to_lowercase().as_str()))
+        {
+            continue;
+        }
+        output.extend(from_node(node, section.as_deref()));
+    }
+    output
+}
+
+/// Extracts the genres listed by a list or table node; other nodes produce nothing.
+pub fn from_node(node: &pwt::Node, section: Option<&str>) -> Vec<ListedGenre> {
+    let mut output = vec![];
+    match node {
+        pwt::Node::UnorderedList { items, .. } | pwt::Node::OrderedList { items, .. } => {
+            from_list_items(items, None, section, &mut output)
+        }
+        pwt::Node::Table { rows, .. } => from_table_rows(rows, section, &mut output),
+        _ => {}
+    }
+    output
+}
+
+fn from_list_items(
+    items: &[pwt::ListItem],
+    parent: Option<&str>,
+    section: Option<&str>,
+    output: &mut Vec<ListedGenre>,
+) {
+    for item in items {
+        let link = first_link(&item.nodes);
+        if let Some(link) = &link {
+            output.push(ListedGenre {
+                parent: parent.map(|p| p.to_string()),
+                link: link.clone(),
+                section: section.map(|s| s.to_string()),
+            });
+        }
+        for node in &item.nodes {
+            if let pwt::Node::UnorderedList { items, .. } | pwt::Node::OrderedList { items, .. } =
+                node
+            {
+                // Nested items are subgenres of the item they're nested under
+                from_list_items(items, link.as_deref().or(parent), section, output);
+            }
+        }
+    }
+}
+
+fn from_table_rows(rows: &[pwt::TableRow], section: Option<&str>, output: &mut Vec<ListedGenre>) {
+    let mut name_column = None;
+    let mut parent_column = None;
+    for row in rows {
+        let is_header = !row.cells.is_empty()
+            && row
+                .cells
+                .iter()
+                .all(|c| matches!(c.type_, pwt::TableCellType::Heading));
+        if is_header {
+            for (i, cell) in row.cells.iter().enumerate() {
+                let text =
+                    nodes_inner_text(&cell.content, &InnerTextConfig::default()).to_lowercase();
+                if ["parent", "origin", "derived from"]
+                    .iter()
+                    .any(|k| text.contains(k))
+                {
+                    parent_column = Some(i);
+                } else if name_column.is_none()
+                    && ["genre", "style", "name"].iter().any(|k| text.contains(k))
+                {
+                    name_column = Some(i);
+                }
+            }
+            continue;
+        }
+
+        let Some(link) = row
+            .cells
+            .get(name_column.unwrap_or(0))
+            .and_then(|cell| first_link(&cell.content))
+        else {
+            continue;
+        };
+        let parents = parent_column
+            .and_then(|i| row.cells.get(i))
+            .map(|cell| get_links_from_nodes(&cell.content))
+            .unwrap_or_default();
+        if parents.is_empty() {
+            output.push(ListedGenre {
+                parent: None,
+                link,
+                section: section.map(|s| s.to_string()),
+            });
+        } else {
+            output.extend(parents.into_iter().map(|parent| ListedGenre {
+                parent: Some(parent),
+                link: link.clone(),
+                section: section.map(|s| s.to_string()),
+            }));
+        }
+    }
+}
+
+/// The target of the first link directly within `nodes`, ignoring nested lists.
+fn first_link(nodes: &[pwt::Node]) -> Option<String> {
+    nodes.iter().find_map(|node| match node {
+        pwt::Node::Link { target, .. } => Some(target.trim().to_string()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listed(wikitext: &str) -> Vec<(Option<String>, String, Option<String>)> {
+        let parsed = pwt_configuration().parse(wikitext).unwrap();
+        from_list_article(&parsed.nodes)
+            .into_iter()
+            .map(|lg| (lg.parent, lg.link, lg.section))
+            .collect()
+    }
+
+    #[test]
+    fn test_is_subgenre_heading() {
+        for heading in [
+            "Subgenres",
+            "Sub-genres and fusions",
+            "Styles",
+            "Styles of jazz",
+            "Regional styles",
+        ] {
+            assert!(is_subgenre_heading(heading), "{heading}");
+        }
+        for heading in [
+            "Lifestyles",
+            "Fashion and lifestyles",
+            "Dance styles",
+            "Stylistic origins",
+        ] {
+            assert!(!is_subgenre_heading(heading), "{heading}");
+        }
+    }
+
+    #[test]
+    fn test_nested_list() {
+        assert_eq!(
+            listed("==House==\n* [[Acid house]]\n* [[Deep house]]\n** [[Lo-fi house]]\n==See also==\n* [[Techno]]"),
+            vec![
+                (None, "Acid house".to_string(), Some("House".to_string())),
+                (None, "Deep house".to_string(), Some("House".to_string())),
+                (
+                    Some("Deep house".to_string()),
+                    "Lo-fi house".to_string(),
+                    Some("House".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table_with_parent_column() {
+        assert_eq!(
+            listed(
+                "==Bass==\n{|\n! Genre !! Parent genres !! Year\n|-\n| [[Dubstep]] || [[UK garage]], [[Dub music|dub]] || 2000s\n|-\n| [[Grime music|Grime]] || || 2000s\n|}"
+            ),
+            vec![
+                (
+                    Some("UK garage".to_string()),
+                    "Dubstep".to_string(),
+                    Some("Bass".to_string())
+                ),
+                (
+                    Some("Dub music".to_string()),
+                    "Dubstep".to_string(),
+                    Some("Bass".to_string())
+                ),
+                (None, "Grime music".to_string(), Some("Bass".to_string())),
+            ]
+        );
+    }
+}
@@ -11,9 +11,29 @@ use std::{
 };
 
 use parse_wiki_text_2 as pwt;
-use wikitext_util::{nodes_inner_text, pwt_configuration, InnerTextConfig, NodeMetadata};
+use wikitext_util::{
+    node_inner_text, nodes_inner_text, pwt_configuration, InnerTextConfig, NodeMetadata,
+};
 
+mod advisories;
+mod article_sections;
+mod assessments;
+mod categories;
+mod citations;
+mod consistency;
 mod data_patches;
+mod hatnotes;
+mod images;
+mod lists;
+mod musical_attributes;
+mod native_names;
+mod navboxes;
+mod node_kinds;
+mod prose;
+mod quality;
+mod samples;
+mod stubs;
+mod suggested_edits;
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 /// A newtype for a Wikipedia page name.
 pub struct PageName {
@@ -121,6 +141,14 @@ impl std::fmt::Display for GenreName {
 struct Config {
     wikipedia_dump_path: PathBuf,
     wikipedia_index_path: PathBuf,
+    /// The categories that genres are tagged with when their categories lead up to them;
+    /// see [`categories::tag_genres`]
+    #[serde(default = "categories::default_roots")]
+    category_roots: Vec<String>,
+    /// The `pages-meta-current` dump files, which include the talk pages that the
+    /// `pages-articles` dump doesn't; see [`assessments::load_or_extract`]
+    #[serde(default)]
+    wikipedia_talk_dump_paths: Vec<PathBuf>,
 }
 fn main() -> anyhow::Result<()> {
     let config: Config = {
@@ -168,9 +196,17 @@ fn main() -> anyhow::Result<()> {
     let offsets_path = output_path.join("offsets.txt");
     let meta_path = output_path.join("meta.toml");
     let genres_path = output_path.join("genres");
+    let list_articles_path = output_path.join("list_articles");
+    let navboxes_path = output_path.join("navboxes");
+    let stub_candidates_path = output_path.join("stub_candidates");
     let redirects_path = output_path.join("all_redirects.toml");
+    let category_parents_path = output_path.join("category_parents.toml");
+    let assessments_path = output_path.join("assessments.toml");
+    let alternative_name_redirects_path = output_path.join("alternative_name_redirects.toml");
     let links_to_articles_path = output_path.join("links_to_articles.toml");
     let processed_genres_path = output_path.join("processed");
+    let stubs_path = output_path.join("stubs.json");
+    let reports_path = output_path.join("reports");
 
     let mixes_path = Path::new("mixes");
 
@@ -180,14 +216,34 @@ fn main() -> anyhow::Result<()> {
 
     let start = std::time::Instant::now();
 
-    let (dump_meta, genres, all_redirects) = extract_genres_and_all_redirects(
+    let (
+        dump_meta,
+        genres,
+        list_article_pages,
+        navbox_pages,
+        stub_candidate_pages,
+        category_parents,
+        all_redirects,
+        alternative_name_redirects,
+    ) = extract_genres_and_all_redirects(
         &config,
         start,
         dump_date,
         &offsets_path,
         &meta_path,
         &genres_path,
+        &list_articles_path,
+        &navboxes_path,
+        &stub_candidates_path,
+        &category_parents_path,
         &redirects_path,
+        &alternative_name_redirects_path,
+    )?;
+    let assessments = assessments::load_or_extract(
+        start,
+        &config.wikipedia_talk_dump_paths,
+        &assessments_path,
+        genres.0.keys().chain(stub_candidate_pages.0.keys()),
     )?;
 
     let mut processed_genres = process_genres(start, &genres, &processed_genres_path)?;
@@ -196,21 +252,73 @@ fn main() -> anyhow::Result<()> {
     if std::env::args().any(|arg| arg == "--populate-mixes") {
         populate_mixes(mixes_path, &dump_meta, &processed_genres)?;
     } else {
-        let links_to_articles = resolve_links_to_articles(
+        let (stubs, all_redirects) = stubs::discover(
+            start,
+            &config,
+            &stubs_path,
+            &genres,
+            &stub_candidate_pages,
+            &processed_genres,
+            all_redirects,
+        )?;
+        let mut links_to_articles = resolve_links_to_articles(
             start,
             &links_to_articles_path,
             &processed_genres,
             all_redirects,
         )?;
 
+        let relationships = consistency::produce_report(
+            start,
+            &dump_meta,
+            &reports_path,
+            &links_to_articles,
+            &processed_genres,
+        )?;
+        suggested_edits::produce_suggestions(
+            start,
+            &reports_path,
+            &genres,
+            &links_to_articles,
+            &processed_genres,
+            &relationships,
+        )?;
+        quality::produce_report(start, &dump_meta, &reports_path, &processed_genres)?;
+
+        let list_articles = lists::process_list_articles(start, &list_article_pages)?;
+        let navboxes = navboxes::process_navboxes(start, &navbox_pages, &genres)?;
+        let category_tags = categories::tag_genres(
+            start,
+            &category_parents,
+            &config.category_roots,
+            &processed_genres,
+            &stubs,
+        );
+        stubs.add_links(&mut links_to_articles);
         produce_data_json(
             start,
             &dump_meta,
             mixes_path,
             &data_path,
+            &genres,
+            &list_articles,
+            &navboxes,
+            &category_tags,
+            &assessments,
+            &stubs,
             &links_to_articles,
+            &alternative_name_redirects,
             &processed_genres,
+            std::env::args().any(|arg| arg == "--suppress-advisory-media"),
         )?;
+        if std::env::args().any(|arg| arg == "--article-sections") {
+            article_sections::produce(
+                start,
+                &website_public_path.join("articles"),
+                &genres,
+                &processed_genres,
+            )?;
+        }
     }
 
     Ok(())
@@ -292,9 +400,16 @@ impl TryFrom<AllRedirects> for HashMap<PageName, PageName> {
     }
 }
 
-/// Given a Wikipedia dump, extract genres and all redirects.
+/// Redirects that have been categorised as alternative names or spellings of their target,
+/// mapped to their (possibly unresolved) target.
+#[derive(Default)]
+struct AlternativeNameRedirects(pub HashMap<PageName, PageName>);
+
+/// Given a Wikipedia dump, extract genres, list articles of genres, navboxes, pages that may be
+/// genres without an infobox, the music category tree, and all redirects.
 ///
 /// We extract all redirects as we may need to resolve redirects to redirects.
+#[allow(clippy::too_many_arguments)]
 fn extract_genres_and_all_redirects(
     config: &Config,
     start: std::time::Instant,
@@ -302,39 +417,73 @@ fn extract_genres_and_all_redirects(
     offsets_path: &Path,
     meta_path: &Path,
     genres_path: &Path,
+    list_articles_path: &Path,
+    navboxes_path: &Path,
+    stub_candidates_path: &Path,
+    category_parents_path: &Path,
     redirects_path: &Path,
-) -> anyhow::Result<(DumpMeta, GenrePages, AllRedirects)> {
+    alternative_name_redirects_path: &Path,
+) -> anyhow::Result<(
+    DumpMeta,
+    GenrePages,
+    GenrePages,
+    GenrePages,
+    GenrePages,
+    categories::CategoryParents,
+    AllRedirects,
+    AlternativeNameRedirects,
+)> {
     // Already exists, just load from file
-    if genres_path.is_dir() && redirects_path.is_file() && meta_path.is_file() {
-        let mut genre_pages = HashMap::default();
-        for entry in std::fs::read_dir(genres_path)? {
-            let path = entry?.path();
-            let Some(file_stem) = path.file_stem() else {
-                continue;
-            };
-            genre_pages.insert(unsanitize_page_name(&file_stem.to_string_lossy()), path);
-        }
+    if genres_path.is_dir()
+        && list_articles_path.is_dir()
+        && navboxes_path.is_dir()
+        && stub_candidates_path.is_dir()
+        && category_parents_path.is_file()
+        && redirects_path.is_file()
+        && alternative_name_redirects_path.is_file()
+        && meta_path.is_file()
+    {
+        let genre_pages = load_wikitext_pages(genres_path)?;
+        let list_article_pages = load_wikitext_pages(list_articles_path)?;
+        let navbox_pages = load_wikitext_pages(navboxes_path)?;
+        let stub_candidate_pages = load_wikitext_pages(stub_candidates_path)?;
         println!(
-            "{:.2}s: loaded all {} genres",
+            "{:.2}s: loaded all {} genres, {} list articles, {} navboxes and {} stub candidates",
             start.elapsed().as_secs_f32(),
-            genre_pages.len()
+            genre_pages.0.len(),
+            list_article_pages.0.len(),
+            navbox_pages.0.len(),
+            stub_candidate_pages.0.len()
         );
 
         let meta = toml::from_str(&std::fs::read_to_string(meta_path)?)?;
+        let category_parents = toml::from_str(&std::fs::read_to_string(category_parents_path)?)?;
+        let alternative_name_redirects =
+            toml::from_str(&std::fs::read_to_string(alternative_name_redirects_path)?)?;
 
         return Ok((
             meta,
-            GenrePages(genre_pages),
+            genre_pages,
+            list_article_pages,
+            navbox_pages,
+            stub_candidate_pages,
+            categories::CategoryParents(category_parents),
             AllRedirects::LazyLoad(redirects_path.to_owned(), start),
+            AlternativeNameRedirects(alternative_name_redirects),
         ));
     }
 
     println!(
-        "Genres directory or redirects file or meta does not exist, extracting from Wikipedia dump"
+        "Genres, list articles, navboxes or stub candidates directory or category parents or redirects file or meta does not exist, extracting from Wikipedia dump"
     );
     let now = std::time::Instant::now();
 
     std::fs::create_dir_all(genres_path).context("Failed to create genres directory")?;
+    std::fs::create_dir_all(list_articles_path)
+        .context("Failed to create list articles directory")?;
+    std::fs::create_dir_all(navboxes_path).context("Failed to create navboxes directory")?;
+    std::fs::create_dir_all(stub_candidates_path)
+        .context("Failed to create stub candidates directory")?;
 
     // Load offsets to allow for multithreaded read
     let offsets = if offsets_path.exists() {
@@ -447,143 +596,92 @@ fn extract_genres_and_all_redirects(
     };
 
     // Iterate over each offset (we'll make this multithreaded later)
-    let (genre_pages, all_redirects) = offsets
+    let extracted = offsets
         .par_iter()
-        .fold(
-            || {
-                (
-                    HashMap::<PageName, PathBuf>::default(),
-                    HashMap::<PageName, PageName>::default(),
-                )
-            },
-            |(mut genre_pages, mut all_redirects), &offset| {
-                let mut reader = quick_xml::reader::Reader::from_reader(std::io::BufReader::new(
-                    // We use an open-ended slice because BzDecoder will terminate after end of stream
-                    bzip2::bufread::BzDecoder::new(&dump_file[offset..]),
-                ));
-                reader.config_mut().trim_text(true);
-
-                let mut buf = vec![];
-
-                let mut title = String::new();
-                let mut recording_title = false;
-
-                let mut text = String::new();
-                let mut recording_text = false;
-
-                let mut timestamp = String::new();
-                let mut recording_timestamp = false;
-
-                loop {
-                    match reader.read_event_into(&mut buf) {
-                        Ok(Event::Eof) => break,
-                        Ok(Event::Start(e)) => {
-                            let name = e.name().0;
-                            if name == b"title" {
-                                title.clear();
-                                recording_title = true;
-                            } else if name == b"text" {
-                                text.clear();
-                                recording_text = true;
-                            } else if name == b"timestamp" {
-                                timestamp.clear();
-                                recording_timestamp = true;
+        .fold(DumpExtraction::default, |mut extracted, &offset| {
+            for_each_page_in_block(&dump_file, offset, |title, timestamp, text| {
+                let page = PageName {
+                    name: title.to_string(),
+                    heading: None,
+                };
+                if text.starts_with("#REDIRECT") {
+                    match parse_redirect_text(&wikipedia_domain, text) {
+                        Ok(redirect) => {
+                            if is_alternative_name_redirect(text) {
+                                extracted
+                                    .alternative_name_redirects
+                                    .insert(page.clone(), redirect.clone());
                             }
+                            extracted.all_redirects.insert(page.clone(), redirect);
                         }
-                        Ok(Event::Text(e)) => {
-                            if recording_title {
-                                title.push_str(&e.unescape().unwrap());
-                            } else if recording_text {
-                                text.push_str(&e.unescape().unwrap());
-                            } else if recording_timestamp {
-                                timestamp.push_str(&e.unescape().unwrap());
-                            }
+                        Err(e) => {
+                            eprintln!("Error parsing redirect: {e:?}");
                         }
-                        Ok(Event::End(e)) => {
-                            if e.name().0 == b"title" {
-                                recording_title = false;
-                            } else if e.name().0 == b"text" {
-                                recording_text = false;
-                            } else if e.name().0 == b"timestamp" {
-                                recording_timestamp = false;
-                            } else if e.name().0 == b"page" {
-                                let page = PageName {
-                                    name: title.clone(),
-                                    heading: None,
-                                };
-                                if text.starts_with("#REDIRECT") {
-                                    match parse_redirect_text(&wikipedia_domain, &text) {
-                                        Ok(redirect) => {
-                                            all_redirects.insert(page.clone(), redirect);
-                                        }
-                                        Err(e) => {
-                                            eprintln!("Error parsing redirect: {e:?}");
-                                        }
-                                    }
-                                } else if text.contains("nfobox music genre") {
-                                    if title.contains(":") {
-                                        continue;
-                                    }
-
-                                    let timestamp = timestamp
-                                        .parse::<jiff::Timestamp>()
-                                        .with_context(|| {
-                                            format!(
-                                                "Failed to parse timestamp {timestamp} for {page}"
-                                            )
-                                        })
-                                        .unwrap();
-
-                                    let output_file_path = genres_path
-                                        .join(format!("{}.wikitext", sanitize_page_name(&page)));
-                                    let output_file = std::fs::File::create(&output_file_path)
-                                        .with_context(|| {
-                                            format!("Failed to create output file for {page}")
-                                        })
-                                        .unwrap();
-                                    let mut output_file = std::io::BufWriter::new(output_file);
-
-                                    writeln!(
-                                        output_file,
-                                        "{}",
-                                        serde_json::to_string(&WikitextHeader { timestamp })
-                                            .unwrap()
-                                    )
-                                    .unwrap();
-                                    write!(output_file, "{text}").unwrap();
-
-                                    genre_pages.insert(page.clone(), output_file_path);
-                                    println!("{:.2}s: {page}", start.elapsed().as_secs_f32());
-                                }
-                            }
-                        }
-                        _ => {}
                     }
-                    buf.clear();
+                } else if text.contains("nfobox music genre") {
+                    if title.contains(":") {
+                        return;
+                    }
+
+                    let output_file_path = save_wikitext_page(genres_path, &page, timestamp, text);
+                    extracted.genre_pages.insert(page.clone(), output_file_path);
+                    println!("{:.2}s: {page}", start.elapsed().as_secs_f32());
+                } else if lists::is_list_article(title) {
+                    let output_file_path =
+                        save_wikitext_page(list_articles_path, &page, timestamp, text);
+                    extracted
+                        .list_article_pages
+                        .insert(page.clone(), output_file_path);
+                    println!("{:.2}s: {page}", start.elapsed().as_secs_f32());
+                } else if navboxes::is_navbox(title, text) {
+                    let output_file_path =
+                        save_wikitext_page(navboxes_path, &page, timestamp, text);
+                    extracted
+                        .navbox_pages
+                        .insert(page.clone(), output_file_path);
+                } else if !title.contains(':') && stubs::has_genre_category(text) {
+                    let output_file_path =
+                        save_wikitext_page(stub_candidates_path, &page, timestamp, text);
+                    extracted
+                        .stub_candidate_pages
+                        .insert(page.clone(), output_file_path);
+                } else if let Some(category) = title.strip_prefix("Category:") {
+                    if categories::is_relevant_category(category) {
+                        extracted
+                            .category_parents
+                            .insert(category.to_string(), categories::from_wikitext(text));
+                    }
                 }
+            });
 
-                (genre_pages, all_redirects)
-            },
-        )
-        .reduce(
-            || {
-                (
-                    HashMap::<PageName, PathBuf>::default(),
-                    HashMap::<PageName, PageName>::default(),
-                )
-            },
-            |(mut genre_pages, mut all_redirects), (new_genre_pages, new_all_redirects)| {
-                genre_pages.extend(new_genre_pages);
-                all_redirects.extend(new_all_redirects);
-                (genre_pages, all_redirects)
-            },
-        );
+            extracted
+        })
+        .reduce(DumpExtraction::default, DumpExtraction::merge);
+    let DumpExtraction {
+        genre_pages,
+        list_article_pages,
+        navbox_pages,
+        stub_candidate_pages,
+        category_parents,
+        all_redirects,
+        alternative_name_redirects,
+    } = extracted;
 
     std::fs::write(
         redirects_path,
         toml::to_string_pretty(&all_redirects)?.as_bytes(),
     )
     .context("Failed to write redirects")?;
+    std::fs::write(
+        category_parents_path,
+        toml::to_string_pretty(&category_parents)?.as_bytes(),
+    )
+    .context("Failed to write category parents")?;
+    std::fs::write(
+        alternative_name_redirects_path,
+        toml::to_string_pretty(&alternative_name_redirects)?.as_bytes(),
+    )
+    .context("Failed to write alternative name redirects")?;
 
     let meta = DumpMeta {
         wikipedia_domain,
@@ -600,10 +698,149 @@ fn extract_genres_and_all_redirects(
     Ok((
         meta,
         GenrePages(genre_pages),
+        GenrePages(list_article_pages),
+        GenrePages(navbox_pages),
+        GenrePages(stub_candidate_pages),
+        categories::CategoryParents(category_parents),
         AllRedirects::InMemory(all_redirects),
+        AlternativeNameRedirects(alternative_name_redirects),
     ))
 }
 
+/// Calls `on_page` with the title, timestamp and text of every page in the bzip2 stream
+/// of the dump that starts at `offset`.
+fn for_each_page_in_block(dump_file: &[u8], offset: usize, on_page: impl FnMut(&str, &str, &str)) {
+    for_each_page(
+        std::io::BufReader::new(
+            // We use an open-ended slice because BzDecoder will terminate after end of stream
+            bzip2::bufread::BzDecoder::new(&dump_file[offset..]),
+        ),
+        on_page,
+    )
+}
+
+/// Calls `on_page` with the title, timestamp and text of every page in the decompressed
+/// dump XML read from `dump`.
+fn for_each_page(dump: impl std::io::BufRead, mut on_page: impl FnMut(&str, &str, &str)) {
+    let mut reader = quick_xml::reader::Reader::from_reader(dump);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = vec![];
+
+    let mut title = String::new();
+    let mut recording_title = false;
+
+    let mut text = String::new();
+    let mut recording_text = false;
+
+    let mut timestamp = String::new();
+    let mut recording_timestamp = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) => {
+                let name = e.name().0;
+                if name == b"title" {
+                    title.clear();
+                    recording_title = true;
+                } else if name == b"text" {
+                    text.clear();
+                    recording_text = true;
+                } else if name == b"timestamp" {
+                    timestamp.clear();
+                    recording_timestamp = true;
+                }
+            }
+            Ok(Event::Text(e)) => {
+                if recording_title {
+                    title.push_str(&e.unescape().unwrap());
+                } else if recording_text {
+                    text.push_str(&e.unescape().unwrap());
+                } else if recording_timestamp {
+                    timestamp.push_str(&e.unescape().unwrap());
+                }
+            }
+            Ok(Event::End(e)) => {
+                if e.name().0 == b"title" {
+                    recording_title = false;
+                } else if e.name().0 == b"text" {
+                    recording_text = false;
+                } else if e.name().0 == b"timestamp" {
+                    recording_timestamp = false;
+                } else if e.name().0 == b"page" {
+                    on_page(&title, &timestamp, &text);
+                }
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+/// The pages extracted from the dump by a single thread, to be merged with those of the others.
+#[derive(Default)]
+struct DumpExtraction {
+    genre_pages: HashMap<PageName, PathBuf>,
+    list_article_pages: HashMap<PageName, PathBuf>,
+    navbox_pages: HashMap<PageName, PathBuf>,
+    stub_candidate_pages: HashMap<PageName, PathBuf>,
+    category_parents: HashMap<String, Vec<String>>,
+    all_redirects: HashMap<PageName, PageName>,
+    alternative_name_redirects: HashMap<PageName, PageName>,
+}
+impl DumpExtraction {
+    fn merge(mut self, other: Self) -> Self {
+        self.genre_pages.extend(other.genre_pages);
+        self.list_article_pages.extend(other.list_article_pages);
+        self.navbox_pages.extend(other.navbox_pages);
+        self.stub_candidate_pages.extend(other.stub_candidate_pages);
+        self.category_parents.extend(other.category_parents);
+        self.all_redirects.extend(other.all_redirects);
+        self.alternative_name_redirects
+            .extend(other.alternative_name_redirects);
+        self
+    }
+}
+
+/// Writes a page's wikitext to `directory`, prefixed with a [`WikitextHeader`], and returns
+/// the path it was written to.
+fn save_wikitext_page(directory: &Path, page: &PageName, timestamp: &str, text: &str) -> PathBuf {
+    let timestamp = timestamp
+        .parse::<jiff::Timestamp>()
+        .with_context(|| format!("Failed to parse timestamp {timestamp} for {page}"))
+        .unwrap();
+
+    let output_file_path = directory.join(format!("{}.wikitext", sanitize_page_name(page)));
+    let output_file = std::fs::File::create(&output_file_path)
+        .with_context(|| format!("Failed to create output file for {page}"))
+        .unwrap();
+    let mut output_file = std::io::BufWriter::new(output_file);
+
+    writeln!(
+        output_file,
+        "{}",
+        serde_json::to_string(&WikitextHeader { timestamp }).unwrap()
+    )
+    .unwrap();
+    write!(output_file, "{text}").unwrap();
+
+    output_file_path
+}
+
+/// Loads the paths of every page previously saved to `directory` by [`save_wikitext_page`].
+fn load_wikitext_pages(directory: &Path) -> anyhow::Result<GenrePages> {
+    let mut pages = HashMap::default();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let Some(file_stem) = path.file_stem() else {
+            continue;
+        };
+        pages.insert(unsanitize_page_name(&file_stem.to_string_lossy()), path);
+    }
+    Ok(GenrePages(pages))
+}
+
 fn extract_domain(url: &str) -> Option<&str> {
     let domain_start = url.find("://")? + 3;
     let domain_end = url[domain_start..].find('/')?;
@@ -708,6 +945,33 @@ fn parse_redirect_text(wikipedia_domain: &str, text: &str) -> Result<PageName, R
     }
 }
 
+/// Returns the lower-cased names of all templates used in a redirect's wikitext,
+/// including those nested within `{{Redirect category shell}}`.
+fn redirect_templates(text: &str) -> Vec<String> {
+    text.split("{{")
+        .skip(1)
+        .filter_map(|s| {
+            let end = s.find(['|', '}'])?;
+            Some(s[..end].trim().to_lowercase())
+        })
+        .collect()
+}
+
+/// Whether a redirect has been categorised as an alternative name or spelling of its target.
+fn is_alternative_name_redirect(text: &str) -> bool {
+    const ALTERNATIVE_NAME_TEMPLATES: &[&str] = &[
+        "r from alternative name",
+        "r from alternate name",
+        "r from other name",
+        "r from other spelling",
+        "r from alternative spelling",
+        "r from alternate spelling",
+    ];
+    redirect_templates(text)
+        .iter()
+        .any(|t| ALTERNATIVE_NAME_TEMPLATES.contains(&t.as_str()))
+}
+
 #[cfg(test)]
 mod extraction_tests {
     use super::*;
@@ -773,6 +1037,30 @@ mod extraction_tests {
         );
     }
 
+    #[test]
+    fn test_alternative_name_redirect() {
+        let text = "#REDIRECT [[Drum and bass]]
+{{Redirect category shell|
+{{R from alternative name}}
+{{R from other capitalisation}}
+}}";
+        assert!(is_alternative_name_redirect(text));
+        assert_eq!(
+            redirect_templates(text),
+            vec![
+                "redirect category shell",
+                "r from alternative name",
+                "r from other capitalisation"
+            ]
+        );
+
+        let text = "#REDIRECT [[UK hard house#Scouse house]]
+{{Redirect category shell|
+{{R to section}}
+}}";
+        assert!(!is_alternative_name_redirect(text));
+    }
+
     #[test]
     fn test_parse_redirect_invalid() {
         let text = "Not a redirect";
@@ -885,8 +1173,23 @@ fn resolve_links_to_articles(
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct ProcessedGenre {
     name: GenreName,
+    /// Other names for this genre from the infobox (after the `<br>` in the name, or `other_names`).
+    /// Redirect-based aliases are added when producing the output.
+    #[serde(default)]
+    aliases: Vec<String>,
+    /// Names in other languages and scripts from language templates in the lead.
+    #[serde(default)]
+    native_names: Vec<native_names::NativeName>,
     page: PageName,
+    /// The position of this genre's infobox among the article's infoboxes, so that it can be
+    /// found again in the original wikitext
+    #[serde(default)]
+    infobox_index: usize,
+    /// The article's `{{Short description}}`, if this genre is the subject of the article.
+    short_description: Option<String>,
     wikitext_description: Option<String>,
+    /// The image from the infobox, or the first image in the lead if the infobox has none
+    lead_image: Option<images::LeadImage>,
     last_revision_date: jiff::Timestamp,
     // the following are unresolved links: we do this
     // so that we can defer link resolution to the end of the pipeline
@@ -895,6 +1198,59 @@ struct ProcessedGenre {
     derivatives: Vec<String>,
     subgenres: Vec<String>,
     fusion_genres: Vec<String>,
+    #[serde(default)]
+    regional_scenes: Vec<String>,
+    #[serde(default)]
+    local_scenes: Vec<String>,
+    /// Relationships from hatnotes on the page or the genre's sections (unresolved links)
+    #[serde(default)]
+    hatnotes: Vec<hatnotes::Hatnote>,
+    /// Representative recordings from `{{Listen}}` templates on the page or the genre's sections
+    #[serde(default)]
+    samples: Vec<samples::Sample>,
+    /// Deduplicated citations for the description and the infobox links
+    #[serde(default)]
+    references: Vec<citations::Citation>,
+    /// The indices of the references cited by each paragraph of the description
+    #[serde(default)]
+    description_references: Vec<Vec<usize>>,
+    /// The indices of the references cited by each infobox link
+    #[serde(default)]
+    link_references: Vec<LinkReferences>,
+    /// Names in the infobox link parameters that aren't linked; these are matched against
+    /// the names of other genres when producing the output
+    #[serde(default)]
+    unlinked_names: Vec<UnlinkedName>,
+    /// Candidate relationships mined from the description's prose (unresolved links)
+    #[serde(default)]
+    prose_relations: Vec<prose::ProseRelation>,
+    /// Genres listed in the genre's "Subgenres" or "Styles" sections (unresolved links)
+    #[serde(default)]
+    listed_subgenres: Vec<lists::ListedGenre>,
+    /// The categories of the page the genre is on, without the `Category:` prefix
+    #[serde(default)]
+    categories: Vec<String>,
+    /// The tempo range stated in the description
+    tempo: Option<musical_attributes::TempoRange>,
+    /// The time signatures stated in the description
+    #[serde(default)]
+    time_signatures: Vec<musical_attributes::TimeSignature>,
+    /// The quality signals of the genre's article; genres processed before these were
+    /// computed don't have them
+    quality: Option<quality::Quality>,
+}
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct UnlinkedName {
+    /// The infobox parameter the name was found in (i.e. `stylistic_origins`)
+    field: String,
+    name: String,
+}
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct LinkReferences {
+    /// The infobox parameter the link was found in (i.e. `stylistic_origins`)
+    field: String,
+    link: String,
+    references: Vec<usize>,
 }
 impl ProcessedGenre {
     pub fn edge_count(&self) -> usize {
@@ -903,8 +1259,42 @@ impl ProcessedGenre {
             + self.subgenres.len()
             + self.fusion_genres.len()
     }
+    /// All scenes, regional and local, that this genre is associated with.
+    pub fn scenes(&self) -> impl Iterator<Item = &String> {
+        self.regional_scenes.iter().chain(self.local_scenes.iter())
+    }
+    /// The indices of the references cited for `link` in the infobox parameter `field`.
+    pub fn link_references_for<'a>(
+        &'a self,
+        field: &'a str,
+        link: &'a str,
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.link_references
+            .iter()
+            .filter(move |lr| lr.field == field && lr.link == link)
+            .flat_map(|lr| lr.references.iter().copied())
+    }
+    /// Records a citation for the paragraph of the description currently being recorded.
+    pub fn add_description_reference(&mut self, description: &str, citation: citations::Citation) {
+        let paragraph = description
+            .split("\n\n")
+            .filter(|p| !p.trim().is_empty())
+            .count()
+            .saturating_sub(1);
+        let index = citations::add_reference(&mut self.references, citation);
+        if self.description_references.len() <= paragraph {
+            self.description_references.resize(paragraph + 1, vec![]);
+        }
+        if !self.description_references[paragraph].contains(&index) {
+            self.description_references[paragraph].push(index);
+        }
+    }
     pub fn update_description(&mut self, description: String) {
-        self.wikitext_description = Some(description.trim().to_string());
+        let description = description.trim().to_string();
+        self.prose_relations = prose::extract_relations(&description);
+        self.tempo = musical_attributes::tempo(&description);
+        self.time_signatures = musical_attributes::time_signatures(&description);
+        self.wikitext_description = Some(description);
     }
     pub fn save(&self, processed_genres_path: &Path) -> anyhow::Result<()> {
         std::fs::write(
@@ -915,6 +1305,64 @@ impl ProcessedGenre {
     }
 }
 struct ProcessedGenres(pub HashMap<PageName, ProcessedGenre>);
+
+fn dump_page_nodes(wikitext: &str, nodes: &[pwt::Node], depth: usize) {
+    for node in nodes {
+        print!("{:indent$}", "", indent = depth * 2);
+        let metadata = NodeMetadata::for_node(node);
+        println!(
+            "{}[{}..{}]: {:?}",
+            metadata.name,
+            metadata.start,
+            metadata.end,
+            &wikitext[metadata.start..metadata.end]
+        );
+        if let Some(children) = metadata.children {
+            dump_page_nodes(wikitext, children, depth + 1);
+        }
+    }
+}
+
+/// This is monstrous.
+/// We are parsing the Wikitext, reconstructing it without the comments, and then parsing it again.
+///
+/// This is necessary as parse-wiki-text has a bug in which it does not recognise headings
+/// where comments immediately follow - i.e.
+///   ===Heading===<!-- Lmao -->
+/// results in `===Heading===` being parsed as text, not a heading.
+///
+/// Ideally, this would be fixed upstream, but that looks like a non-trivial fix, and
+/// compute and memory is cheap, so... here we go.
+fn remove_comments_from_wikitext_the_painful_way(
+    pwt_configuration: &pwt::Configuration,
+    dump_page: Option<&str>,
+    page: &PageName,
+    wikitext: &str,
+) -> String {
+    let parsed_wikitext = pwt_configuration
+        .parse_with_timeout(wikitext, std::time::Duration::from_secs(1))
+        .unwrap_or_else(|e| panic!("failed to parse wikitext ({page}): {e:?}"));
+
+    let mut new_wikitext = wikitext.to_string();
+    let mut comment_ranges = vec![];
+
+    if dump_page.is_some_and(|s| s == page.name) {
+        println!("--- BEFORE ---");
+        dump_page_nodes(wikitext, &parsed_wikitext.nodes, 0);
+    }
+
+    for node in &parsed_wikitext.nodes {
+        if let pwt::Node::Comment { start, end, .. } = node {
+            comment_ranges.push((*start, *end));
+        }
+    }
+
+    for (start, end) in comment_ranges.into_iter().rev() {
+        new_wikitext.replace_range(start..end, "");
+    }
+    new_wikitext
+}
+
 /// Given raw genre wikitext, extract the relevant information and save it to file.
 fn process_genres(
     start: std::time::Instant,
@@ -950,63 +1398,6 @@ fn process_genres(
 
     let dump_page = std::env::var("DUMP_PAGE").ok();
 
-    fn dump_page_nodes(wikitext: &str, nodes: &[pwt::Node], depth: usize) {
-        for node in nodes {
-            print!("{:indent$}", "", indent = depth * 2);
-            let metadata = NodeMetadata::for_node(node);
-            println!(
-                "{}[{}..{}]: {:?}",
-                metadata.name,
-                metadata.start,
-                metadata.end,
-                &wikitext[metadata.start..metadata.end]
-            );
-            if let Some(children) = metadata.children {
-                dump_page_nodes(wikitext, children, depth + 1);
-            }
-        }
-    }
-
-    /// This is monstrous.
-    /// We are parsing the Wikitext, reconstructing it without the comments, and then parsing it again.
-    ///
-    /// This is necessary as parse-wiki-text has a bug in which it does not recognise headings
-    /// where comments immediately follow - i.e.
-    ///   ===Heading===<!-- Lmao -->
-    /// results in `===Heading===` being parsed as text, not a heading.
-    ///
-    /// Ideally, this would be fixed upstream, but that looks like a non-trivial fix, and
-    /// compute and memory is cheap, so... here we go.
-    fn remove_comments_from_wikitext_the_painful_way(
-        pwt_configuration: &pwt::Configuration,
-        dump_page: Option<&str>,
-        page: &PageName,
-        wikitext: &str,
-    ) -> String {
-        let parsed_wikitext = pwt_configuration
-            .parse_with_timeout(wikitext, std::time::Duration::from_secs(1))
-            .unwrap_or_else(|e| panic!("failed to parse wikitext ({page}): {e:?}"));
-
-        let mut new_wikitext = wikitext.to_string();
-        let mut comment_ranges = vec![];
-
-        if dump_page.is_some_and(|s| s == page.name) {
-            println!("--- BEFORE ---");
-            dump_page_nodes(wikitext, &parsed_wikitext.nodes, 0);
-        }
-
-        for node in &parsed_wikitext.nodes {
-            if let pwt::Node::Comment { start, end, .. } = node {
-                comment_ranges.push((*start, *end));
-            }
-        }
-
-        for (start, end) in comment_ranges.into_iter().rev() {
-            new_wikitext.replace_range(start..end, "");
-        }
-        new_wikitext
-    }
-
     for (original_page, path) in genres.iter() {
         let wikitext = std::fs::read_to_string(path)?;
         let (wikitext_header, wikitext) = wikitext.split_once("\n").unwrap();
@@ -1021,6 +1412,9 @@ fn process_genres(
         let parsed_wikitext = pwt_configuration
             .parse_with_timeout(&wikitext, std::time::Duration::from_secs(1))
             .unwrap_or_else(|e| panic!("failed to parse wikitext ({original_page}): {e:?}"));
+        let named_refs = citations::collect_named_refs(&wikitext);
+        let page_categories = categories::from_nodes(&parsed_wikitext.nodes);
+        let article_quality = quality::Quality::from_article(&wikitext);
         if dump_page
             .as_deref()
             .is_some_and(|s| s == original_page.name)
@@ -1038,10 +1432,22 @@ fn process_genres(
             last_end.take().filter(|&end| end < start).unwrap_or(start)
         }
         let mut last_heading = None;
+        let mut short_description = None;
+        // Hatnotes and samples since the last heading that haven't been attributed to a genre yet
+        let mut pending_hatnotes = vec![];
+        let mut pending_samples = vec![];
+        let mut infobox_count = 0;
 
         let mut processed_genre: Option<ProcessedGenre> = None;
 
         for node in &parsed_wikitext.nodes {
+            if let (Some(processed_genre), Some(heading)) = (&mut processed_genre, &last_heading) {
+                if lists::is_subgenre_heading(heading) {
+                    processed_genre
+                        .listed_subgenres
+                        .extend(lists::from_node(node, Some(heading.as_str())));
+                }
+            }
             match node {
                 pwt::Node::Template {
                     name,
@@ -1076,7 +1482,7 @@ fn process_genres(
                         }
 
                         fn is_ignorable_template(template_name: &str) -> bool {
-                            template_name.starts_with("use")
+                            template_name.starts_with("use") || template_name == "short description"
                         }
 
                         if !pause_recording_description
@@ -1088,9 +1494,56 @@ fn process_genres(
                                 &wikitext[start_including_last_end(&mut last_end, *start)..*end],
                             );
                         }
+
+                        if let Some(processed_genre) = processed_genre
+                            .as_mut()
+                            .filter(|_| !pause_recording_description)
+                        {
+                            native_names::extract_from_template(
+                                &template_name,
+                                parameters,
+                                &mut processed_genre.native_names,
+                            );
+                        }
                     }
                     last_end = Some(*end);
 
+                    let new_hatnotes = hatnotes::from_template(&template_name, parameters);
+                    if !new_hatnotes.is_empty() {
+                        pending_hatnotes.extend(new_hatnotes);
+                        continue;
+                    }
+
+                    let new_samples = samples::from_template(
+                        &wikitext,
+                        &template_name,
+                        parameters,
+                        last_heading.as_deref(),
+                    );
+                    if !new_samples.is_empty() {
+                        pending_samples.extend(new_samples);
+                        continue;
+                    }
+
+                    if template_name == "short description" {
+                        let new_short_description = parameters
+                            .iter()
+                            .find(|p| p.name.is_none())
+                            .map(|p| nodes_inner_text(&p.value, &InnerTextConfig::default()))
+                            .filter(|s| !s.is_empty() && !s.eq_ignore_ascii_case("none"));
+                        // The short description is usually at the top of the article, but if it
+                        // follows the infobox, attribute it to the genre we've just started.
+                        match &mut processed_genre {
+                            Some(processed_genre)
+                                if processed_genre.short_description.is_none() =>
+                            {
+                                processed_genre.short_description = new_short_description;
+                            }
+                            _ => short_description = new_short_description,
+                        }
+                        continue;
+                    }
+
                     if template_name != "infobox music genre" {
                         continue;
                     }
@@ -1150,40 +1603,138 @@ fn process_genres(
                         }
                     }
 
-                    let stylistic_origins = parameters
-                        .get("stylistic_origins")
-                        .map(|ns| get_links_from_nodes(ns))
-                        .unwrap_or_default();
-                    let derivatives = parameters
-                        .get("derivatives")
-                        .map(|ns| get_links_from_nodes(ns))
+                    let mut aliases = parameters
+                        .get("name")
+                        .map(|nodes| {
+                            nodes
+                                .split(is_br)
+                                .skip(1)
+                                .filter_map(|ns| {
+                                    clean_name(&nodes_inner_text(ns, &InnerTextConfig::default()))
+                                })
+                                .collect::<Vec<_>>()
+                        })
                         .unwrap_or_default();
-                    let subgenres = parameters
-                        .get("subgenres")
+                    aliases.extend(
+                        parameters
+                            .get("other_names")
+                            .map(|ns| get_names_from_nodes(ns))
+                            .unwrap_or_default(),
+                    );
+
+                    let mut references = vec![];
+                    let mut link_references = vec![];
+                    let mut get_links_and_references = |field: &str| {
+                        parameters
+                            .get(field)
+                            .map(|ns| {
+                                get_links_and_references_from_nodes(
+                                    &pwt_configuration,
+                                    &wikitext,
+                                    ns,
+                                    &named_refs,
+                                    field,
+                                    &mut references,
+                                    &mut link_references,
+                                )
+                            })
+                            .unwrap_or_default()
+                    };
+                    let stylistic_origins = get_links_and_references("stylistic_origins");
+                    let derivatives = get_links_and_references("derivatives");
+                    let subgenres = get_links_and_references("subgenres");
+                    let fusion_genres = get_links_and_references("fusiongenres");
+                    let unlinked_names = [
+                        "stylistic_origins",
+                        "derivatives",
+                        "subgenres",
+                        "fusiongenres",
+                    ]
+                    .into_iter()
+                    .flat_map(|field| {
+                        parameters
+                            .get(field)
+                            .map(|ns| get_unlinked_names_from_nodes(ns))
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(move |name| UnlinkedName {
+                                field: field.to_string(),
+                                name,
+                            })
+                    })
+                    .collect();
+                    let regional_scenes = parameters
+                        .get("regional_scenes")
                         .map(|ns| get_links_from_nodes(ns))
                         .unwrap_or_default();
-                    let fusion_genres = parameters
-                        .get("fusiongenres")
+                    let local_scenes = parameters
+                        .get("local_scenes")
                         .map(|ns| get_links_from_nodes(ns))
                         .unwrap_or_default();
 
+                    let lead_image = parameters.get("image").and_then(|image| {
+                        images::from_infobox(
+                            &wikitext,
+                            image,
+                            parameters.get("caption").copied(),
+                            parameters.get("alt").copied(),
+                        )
+                    });
+
                     genre_count += 1;
                     stylistic_origin_count += stylistic_origins.len();
                     derivative_count += derivatives.len();
 
                     processed_genre = Some(ProcessedGenre {
                         name: name.clone(),
+                        aliases,
+                        native_names: vec![],
                         page: original_page.with_opt_heading(last_heading.clone()),
+                        infobox_index: infobox_count,
+                        // The short description describes the article as a whole, so it's only
+                        // applicable to genres that aren't under a heading.
+                        short_description: short_description
+                            .clone()
+                            .filter(|_| last_heading.is_none()),
                         wikitext_description: None,
+                        lead_image,
                         last_revision_date: wikitext_header.timestamp,
                         stylistic_origins,
                         derivatives,
                         subgenres,
                         fusion_genres,
+                        regional_scenes,
+                        local_scenes,
+                        // Hatnotes and samples in this section before the infobox belong to this genre
+                        hatnotes: std::mem::take(&mut pending_hatnotes),
+                        samples: std::mem::take(&mut pending_samples),
+                        references,
+                        description_references: vec![],
+                        link_references,
+                        unlinked_names,
+                        prose_relations: vec![],
+                        listed_subgenres: vec![],
+                        categories: page_categories.clone(),
+                        tempo: None,
+                        time_signatures: vec![],
+                        quality: Some(article_quality.clone().with_infobox(&parameters)),
                     });
+                    infobox_count += 1;
                     description = Some(String::new());
                 }
-                pwt::Node::StartTag { name, end, .. } if name == "ref" => {
+                pwt::Node::StartTag { name, start, end } if name == "ref" => {
+                    if let (Some(processed_genre), Some(description)) =
+                        (&mut processed_genre, &description)
+                    {
+                        if let Some(citation) = citations::Citation::from_ref_at(
+                            &pwt_configuration,
+                            &wikitext,
+                            *start,
+                            &named_refs,
+                        ) {
+                            processed_genre.add_description_reference(description, citation);
+                        }
+                    }
                     pause_recording_description = true;
                     last_end = Some(*end);
                 }
@@ -1191,8 +1742,22 @@ fn process_genres(
                     pause_recording_description = false;
                     last_end = Some(*end);
                 }
-                pwt::Node::Tag { name, end, .. } if name == "ref" => {
-                    // Explicitly ignore body of a ref tag
+                pwt::Node::Tag {
+                    name, start, end, ..
+                } if name == "ref" => {
+                    // Explicitly ignore body of a ref tag in the description, but record it as a citation
+                    if let (Some(processed_genre), Some(description)) =
+                        (&mut processed_genre, &description)
+                    {
+                        if let Some(citation) = citations::Citation::from_ref_at(
+                            &pwt_configuration,
+                            &wikitext,
+                            *start,
+                            &named_refs,
+                        ) {
+                            processed_genre.add_description_reference(description, citation);
+                        }
+                    }
                     last_end = Some(*end);
                 }
                 pwt::Node::Bold { end, start }
@@ -1237,6 +1802,11 @@ fn process_genres(
                 }
                 pwt::Node::Heading { nodes, end, .. } => {
                     if let Some(processed_genre) = &mut processed_genre {
+                        // Hatnotes and samples in the section that just ended belong to the genre
+                        // being processed
+                        processed_genre.hatnotes.append(&mut pending_hatnotes);
+                        processed_genre.samples.append(&mut pending_samples);
+
                         // We continue going if the description so far is empty: some infoboxes are placed
                         // before a heading, with the content following after the heading, so we offer
                         // this as an opportunity to capture that content.
@@ -1247,17 +1817,35 @@ fn process_genres(
                         } else {
                             last_end = Some(*end);
                         }
+                    } else {
+                        // Without a genre, they belong to the lead or a section without an
+                        // infobox, not to the next genre
+                        pending_hatnotes.clear();
+                        pending_samples.clear();
                     }
 
                     last_heading = Some(nodes_inner_text(nodes, &InnerTextConfig::default()));
                 }
-                pwt::Node::Image { end, .. } | pwt::Node::Comment { end, .. } => {
+                pwt::Node::Image { start, end, .. } => {
+                    // Fall back to the first image in the lead if the infobox doesn't have one
+                    if let Some(processed_genre) = processed_genre
+                        .as_mut()
+                        .filter(|pg| description.is_some() && pg.lead_image.is_none())
+                    {
+                        processed_genre.lead_image =
+                            images::from_image_wikitext(&wikitext[*start..*end]);
+                    }
+                    last_end = Some(*end);
+                }
+                pwt::Node::Comment { end, .. } => {
                     last_end = Some(*end);
                 }
             }
         }
 
         if let Some(processed_genre) = &mut processed_genre {
+            processed_genre.hatnotes.append(&mut pending_hatnotes);
+            processed_genre.samples.append(&mut pending_samples);
             let new_page = processed_genre.page.clone();
             if let Some(description) = description.take() {
                 processed_genre.update_description(description);
@@ -1282,6 +1870,44 @@ fn process_genres(
     Ok(ProcessedGenres(processed_genres))
 }
 
+#[cfg(test)]
+mod process_genres_tests {
+    use super::*;
+
+    #[test]
+    fn test_lead_hatnotes_and_samples_stay_in_the_lead() {
+        let directory = std::env::temp_dir().join("genresinspace_process_genres_tests");
+        let _ = std::fs::remove_dir_all(&directory);
+        let raw_path = directory.join("raw");
+        std::fs::create_dir_all(&raw_path).unwrap();
+
+        let page = PageName::new("Dub music", None);
+        let path = save_wikitext_page(
+            &raw_path,
+            &page,
+            "2025-01-01T00:00:00Z",
+            "{{About|the genre|the film|Dub (film)}}\n{{Listen|filename=Dub.ogg|title=Dub}}\n'''Dub''' is a genre.\n== Jamaican dub ==\n{{Distinguish|Dubstep}}\n{{Infobox music genre\n| name = Jamaican dub\n| stylistic_origins = [[Reggae]]\n}}\n'''Jamaican dub''' emerged in the 1960s.",
+        );
+        let processed_genres = process_genres(
+            std::time::Instant::now(),
+            &GenrePages(HashMap::from([(page.clone(), path)])),
+            &directory.join("processed"),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let genre = &processed_genres.0[&page.with_opt_heading(Some("Jamaican dub".to_string()))];
+        assert_eq!(
+            genre.hatnotes,
+            vec![hatnotes::Hatnote {
+                ty: hatnotes::RelationType::ConfusedWith,
+                target: "Dubstep".to_string(),
+            }]
+        );
+        assert!(genre.samples.is_empty());
+    }
+}
+
 fn remove_ignored_pages_and_detect_duplicates(processed_genres: &mut ProcessedGenres) {
     for page in data_patches::pages_to_ignore() {
         processed_genres.0.remove(&page);
@@ -1327,18 +1953,24 @@ fn populate_mixes(
     needs_filling.reverse();
 
     for pg in needs_filling {
-        let mut description = nodes_inner_text(
-            &pwt_configuration
-                .parse(pg.wikitext_description.as_deref().unwrap_or_default())
-                .unwrap()
-                .nodes,
-            &InnerTextConfig {
-                stop_after_br: true,
-            },
-        );
-        if let Some(dot_idx) = description.find('.') {
-            description.truncate(dot_idx + 1);
-        }
+        let description = match &pg.short_description {
+            Some(short_description) => short_description.clone(),
+            None => {
+                let mut description = nodes_inner_text(
+                    &pwt_configuration
+                        .parse(pg.wikitext_description.as_deref().unwrap_or_default())
+                        .unwrap()
+                        .nodes,
+                    &InnerTextConfig {
+                        stop_after_br: true,
+                    },
+                );
+                if let Some(dot_idx) = description.find('.') {
+                    description.truncate(dot_idx + 1);
+                }
+                description
+            }
+        };
 
         let wikipedia_page_link = format!(
             "https://{}/wiki/{}",
@@ -1383,7 +2015,17 @@ struct FrontendData {
     wikipedia_db_name: String,
     dump_date: String,
     nodes: Vec<NodeData>,
-    edges: BTreeSet<EdgeData>,
+    edges: Vec<EdgeData>,
+    /// Relationships from hatnotes; these are kept separate from the infobox-derived `edges`
+    relations: BTreeSet<RelationData>,
+    /// Fusion genres as hyperedges over all of their parents; each parent is still connected
+    /// to the fusion by an edge in `edges`
+    fusions: Vec<FusionData>,
+    /// Candidate relationships mined from the descriptions; these are less certain than `edges`,
+    /// so they're kept separate
+    prose_edges: Vec<ProseEdgeData>,
+    /// Curated clusters of genres from the groups of navboxes used on genre pages
+    navbox_groups: Vec<NavboxGroupData>,
     /// This is a separate field as `LinksToArticles` has already resolved
     /// redirects, which we wouldn't know about on the client
     links_to_page_ids: BTreeMap<String, PageDataId>,
@@ -1393,41 +2035,272 @@ struct FrontendData {
 struct NodeData {
     id: PageDataId,
     page_title: PageName,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_description: Option<String>,
     wikitext_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lead_image: Option<images::LeadImage>,
     label: GenreName,
-    last_revision_date: jiff::Timestamp,
+    /// Other names for this node, from the infobox and from alternative-name redirects
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    native_names: Vec<native_names::NativeName>,
+    /// Scene nodes are not backed by a processed genre, so they have no revision date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_revision_date: Option<jiff::Timestamp>,
+    /// Whether this node is a scene that was referenced by a genre, but is not a genre itself
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    scene: bool,
+    /// Whether this node is a genre without an infobox, so it only has a name and description
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    stub: bool,
+    #[serde(default, skip_serializing_if = "node_kinds::NodeKind::is_genre")]
+    kind: node_kinds::NodeKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    advisory: Option<advisories::Advisory>,
+    /// Whether the genre's mixes and samples were left out because of its advisory, so that
+    /// the frontend doesn't ask for mixes to be contributed for it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    media_suppressed: bool,
+    /// Tags derived from the genre's categories (i.e. "Music genres of Brazil")
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    tags: BTreeSet<String>,
+    /// The tempo range stated in the description, in beats per minute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tempo: Option<musical_attributes::TempoRange>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    time_signatures: Vec<musical_attributes::TimeSignature>,
+    /// Scene and stub nodes aren't backed by an infobox, so they have no quality signals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    quality: Option<quality::Quality>,
+    /// The highest WikiProject quality class given to the article on its talk page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    assessment_class: Option<assessments::AssessmentClass>,
+    /// The highest WikiProject importance given to the article on its talk page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    importance: Option<assessments::Importance>,
     #[serde(skip_serializing_if = "Option::is_none")]
     mixes: Option<GenreMixes>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    samples: Vec<samples::Sample>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    references: Vec<citations::Citation>,
+    /// The indices into `references` cited by each paragraph of the description
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    description_references: Vec<Vec<usize>>,
     edges: BTreeSet<usize>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    relations: BTreeSet<usize>,
+    /// The indices of the fusions this node is either the fusion of, or a member of
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    fusions: BTreeSet<usize>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    prose_edges: BTreeSet<usize>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    navbox_groups: BTreeSet<usize>,
 }
-#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
 enum EdgeType {
     Derivative,
     Subgenre,
     FusionGenre,
+    Scene,
 }
-#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Serialize, Deserialize)]
 struct EdgeData {
     source: PageDataId,
     target: PageDataId,
     ty: EdgeType,
+    /// Which infobox fields this edge came from; an edge described by both of its pages
+    /// will have an entry for each of them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    provenance: Vec<EdgeProvenance>,
+    /// Whether this edge was inferred by a heuristic, rather than described by either page
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    inferred: bool,
+    /// Whether this edge was only found by matching unlinked text in an infobox to a genre's
+    /// name, rather than through a link
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    text_matched: bool,
+    /// The lists this edge was found in, either in a genre's "Subgenres" or "Styles" section
+    /// or in a list article
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    listings: Vec<EdgeListing>,
+    /// Whether this edge was only found in lists, which are less reliable than infoboxes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    listed: bool,
+    /// Citations for this edge from the infoboxes of either page
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    references: BTreeSet<EdgeReference>,
+    /// Sentences from the article of either end that link to the other end
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    evidence: Vec<EdgeEvidence>,
+}
+impl EdgeData {
+    /// Records that this edge was described by the infobox of `page`, in `field` at `position`.
+    fn add_provenance(&mut self, page: PageDataId, field: &str, position: usize) -> &mut Self {
+        self.push_provenance(EdgeProvenance {
+            page,
+            field: field.to_string(),
+            position,
+            text_matched: false,
+        })
+    }
+    /// Like [`EdgeData::add_provenance`], but for an unlinked name that was matched to a genre;
+    /// `position` is the position of the name amongst the infobox's unlinked names.
+    fn add_text_matched_provenance(
+        &mut self,
+        page: PageDataId,
+        field: &str,
+        position: usize,
+    ) -> &mut Self {
+        self.push_provenance(EdgeProvenance {
+            page,
+            field: field.to_string(),
+            position,
+            text_matched: true,
+        })
+    }
+    fn push_provenance(&mut self, provenance: EdgeProvenance) -> &mut Self {
+        self.provenance.push(provenance);
+        self.inferred = false;
+        self.text_matched = self.provenance.iter().all(|p| p.text_matched);
+        self.listed = false;
+        self
+    }
+    /// Records that this edge was found in a list on `page`, under `section`.
+    fn add_listing(&mut self, page: &PageName, section: Option<&str>) -> &mut Self {
+        let listing = EdgeListing {
+            page: page.clone(),
+            section: section.map(|s| s.to_string()),
+        };
+        if !self.listings.contains(&listing) {
+            self.listings.push(listing);
+        }
+        self.inferred = false;
+        self.listed = self.provenance.is_empty();
+        self
+    }
+}
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct EdgeProvenance {
+    /// The page whose infobox describes this edge
+    page: PageDataId,
+    /// The infobox parameter (i.e. `stylistic_origins`)
+    field: String,
+    /// The position of the link within the parameter's list
+    position: usize,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    text_matched: bool,
+}
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct EdgeListing {
+    /// The genre article or list article the list is in
+    page: PageName,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    section: Option<String>,
+}
+#[derive(Debug, Serialize, Deserialize)]
+struct EdgeEvidence {
+    /// The end of the edge whose article the sentence is from
+    page: PageDataId,
+    /// Wikitext, without refs
+    sentence: String,
+}
+/// The maximum number of evidence sentences kept for each edge.
+const MAX_EDGE_EVIDENCE: usize = 3;
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+/// A reference to a citation in the `references` of the node with ID `page`.
+struct EdgeReference {
+    page: PageDataId,
+    reference: usize,
+}
+/// Edges keyed by their endpoints and type, so that a relationship described by both pages
+/// is only emitted once, with the details from both merged.
+#[derive(Default)]
+struct Edges(BTreeMap<(PageDataId, PageDataId, EdgeType), EdgeData>);
+impl Edges {
+    fn insert(&mut self, source: PageDataId, target: PageDataId, ty: EdgeType) -> &mut EdgeData {
+        self.0
+            .entry((source, target, ty))
+            .or_insert_with(|| EdgeData {
+                source,
+                target,
+                ty,
+                provenance: vec![],
+                inferred: false,
+                text_matched: false,
+                listings: vec![],
+                listed: false,
+                references: BTreeSet::new(),
+                evidence: vec![],
+            })
+    }
+}
+#[derive(Debug, Serialize, Deserialize)]
+/// A candidate edge mined from a sentence in the description of the genre with ID `page`.
+struct ProseEdgeData {
+    source: PageDataId,
+    target: PageDataId,
+    ty: EdgeType,
+    page: PageDataId,
+    /// Wikitext
+    sentence: String,
+    confidence: f32,
+}
+#[derive(Debug, Serialize, Deserialize)]
+/// A fusion genre, grouped with every genre it combines.
+struct FusionData {
+    fusion: PageDataId,
+    /// The genres that list the fusion in their `fusiongenres`, as well as the genres in the
+    /// fusion's own `stylistic_origins`
+    members: BTreeSet<PageDataId>,
+}
+#[derive(Debug, Serialize, Deserialize)]
+/// A group of a navbox (i.e. "Eras" in `{{Rock music}}`), with every genre it links to.
+struct NavboxGroupData {
+    /// The navbox's template page
+    navbox: PageName,
+    title: String,
+    group: String,
+    members: BTreeSet<PageDataId>,
+}
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+struct RelationData {
+    source: PageDataId,
+    target: PageDataId,
+    ty: hatnotes::RelationType,
 }
 
 /// Given processed genres, produce a graph and save it to file to be rendered by the website.
+#[allow(clippy::too_many_arguments)]
 fn produce_data_json(
     start: std::time::Instant,
     dump_meta: &DumpMeta,
     mixes_path: &Path,
     data_path: &Path,
+    genres: &GenrePages,
+    list_articles: &[lists::ListArticle],
+    navboxes: &[navboxes::Navbox],
+    category_tags: &categories::CategoryTags,
+    assessments: &assessments::Assessments,
+    stubs: &stubs::Stubs,
     links_to_articles: &LinksToArticles,
+    alternative_name_redirects: &AlternativeNameRedirects,
     processed_genres: &ProcessedGenres,
+    suppress_advisory_media: bool,
 ) -> anyhow::Result<()> {
     let mut graph = FrontendData {
         wikipedia_domain: dump_meta.wikipedia_domain.clone(),
         wikipedia_db_name: dump_meta.wikipedia_db_name.clone(),
         dump_date: dump_meta.dump_date.to_string(),
         nodes: vec![],
-        edges: BTreeSet::new(),
+        edges: vec![],
+        relations: BTreeSet::new(),
+        fusions: vec![],
+        prose_edges: vec![],
+        navbox_groups: vec![],
         links_to_page_ids: BTreeMap::new(),
         max_degree: 0,
     };
@@ -1437,23 +2310,82 @@ fn produce_data_json(
 
     let mut page_to_id = HashMap::new();
 
+    let mut redirect_aliases: HashMap<PageName, Vec<String>> = HashMap::new();
+    for redirect in alternative_name_redirects.0.keys() {
+        if let Some(page) = links_to_articles.map(&redirect.to_string()) {
+            redirect_aliases
+                .entry(page)
+                .or_default()
+                .push(redirect.name.clone());
+        }
+    }
+
+    let kind_overrides = data_patches::node_kinds();
+    let advisory_overrides = data_patches::advisories();
+
     // First pass: create nodes
     for page in &node_order {
         let processed_genre = &processed_genres.0[page];
         let id = PageDataId(graph.nodes.len());
 
+        let mut aliases = processed_genre.aliases.clone();
+        if let Some(redirect_aliases) = redirect_aliases.get_mut(page) {
+            redirect_aliases.sort();
+            aliases.extend(redirect_aliases.iter().cloned());
+        }
+        // Remove aliases that only differ from the label or each other by case
+        let mut seen = HashSet::from([processed_genre.name.0.to_lowercase()]);
+        aliases.retain(|alias| seen.insert(alias.to_lowercase()));
+
+        let advisory = advisory_overrides
+            .get(page)
+            .copied()
+            .unwrap_or_else(|| advisories::from_categories(&processed_genre.categories));
+        // Genres with an advisory are kept, but their media can be left out of the output
+        let suppress_media = suppress_advisory_media && advisory.is_some();
+
         let mixes = std::fs::read_to_string(mixes_path.join(sanitize_page_name(page)))
             .ok()
+            .filter(|_| !suppress_media)
             .map(|f| GenreMixes::parse(&f));
 
         let node = NodeData {
             id,
             page_title: page.clone(),
+            short_description: processed_genre.short_description.clone(),
             wikitext_description: processed_genre.wikitext_description.clone(),
+            lead_image: processed_genre.lead_image.clone(),
             label: processed_genre.name.clone(),
-            last_revision_date: processed_genre.last_revision_date,
+            aliases,
+            native_names: processed_genre.native_names.clone(),
+            last_revision_date: Some(processed_genre.last_revision_date),
+            scene: false,
+            stub: false,
+            kind: kind_overrides
+                .get(page)
+                .copied()
+                .unwrap_or_else(|| node_kinds::classify(page, Some(processed_genre))),
+            advisory,
+            media_suppressed: suppress_media,
+            tags: category_tags.0.get(page).cloned().unwrap_or_default(),
+            tempo: processed_genre.tempo,
+            time_signatures: processed_genre.time_signatures.clone(),
+            quality: processed_genre.quality.clone(),
+            assessment_class: assessments.get(page).and_then(|a| a.class),
+            importance: assessments.get(page).and_then(|a| a.importance),
             mixes,
+            samples: if suppress_media {
+                vec![]
+            } else {
+                processed_genre.samples.clone()
+            },
+            references: processed_genre.references.clone(),
+            description_references: processed_genre.description_references.clone(),
             edges: BTreeSet::new(),
+            relations: BTreeSet::new(),
+            fusions: BTreeSet::new(),
+            navbox_groups: BTreeSet::new(),
+            prose_edges: BTreeSet::new(),
         };
 
         graph.nodes.push(node);
@@ -1463,7 +2395,136 @@ fn produce_data_json(
         page_to_id.entry(page_without_heading).or_insert(id);
     }
 
-    // Second pass: create edges
+    // Create stub nodes for genres without an infobox
+    for stub in &stubs.stubs {
+        if page_to_id.contains_key(&stub.page) {
+            continue;
+        }
+        let id = PageDataId(graph.nodes.len());
+        graph.nodes.push(NodeData {
+            id,
+            page_title: stub.page.clone(),
+            short_description: stub.short_description.clone(),
+            wikitext_description: stub.wikitext_description.clone(),
+            lead_image: None,
+            label: GenreName(stub.name.clone()),
+            aliases: vec![],
+            native_names: vec![],
+            last_revision_date: Some(stub.last_revision_date),
+            scene: false,
+            stub: true,
+            kind: kind_overrides
+                .get(&stub.page)
+                .copied()
+                .unwrap_or_else(|| node_kinds::classify(&stub.page, None)),
+            advisory: advisory_overrides
+                .get(&stub.page)
+                .copied()
+                .unwrap_or_else(|| advisories::from_categories(&stub.categories)),
+            media_suppressed: false,
+            tags: category_tags.0.get(&stub.page).cloned().unwrap_or_default(),
+            tempo: stub
+                .wikitext_description
+                .as_deref()
+                .and_then(musical_attributes::tempo),
+            time_signatures: stub
+                .wikitext_description
+                .as_deref()
+                .map(musical_attributes::time_signatures)
+                .unwrap_or_default(),
+            quality: None,
+            assessment_class: assessments.get(&stub.page).and_then(|a| a.class),
+            importance: assessments.get(&stub.page).and_then(|a| a.importance),
+            mixes: None,
+            samples: vec![],
+            references: vec![],
+            description_references: vec![],
+            edges: BTreeSet::new(),
+            relations: BTreeSet::new(),
+            fusions: BTreeSet::new(),
+            navbox_groups: BTreeSet::new(),
+            prose_edges: BTreeSet::new(),
+        });
+        page_to_id.insert(stub.page.clone(), id);
+    }
+
+    // Second pass: create nodes for scenes that aren't genres in their own right.
+    // Scenes that resolve to a genre are linked to that genre instead.
+    let scene_pages = processed_genres
+        .0
+        .values()
+        .flat_map(|pg| pg.scenes())
+        .filter(|scene| links_to_articles.map(scene).is_none())
+        .map(|scene| (scene.to_lowercase(), scene_page_name(scene)))
+        .collect::<BTreeMap<_, _>>();
+    let mut scene_link_to_id = HashMap::new();
+    for (link, scene_page) in scene_pages {
+        if let Some(id) = page_to_id.get(&scene_page) {
+            scene_link_to_id.insert(link, *id);
+            continue;
+        }
+
+        let id = PageDataId(graph.nodes.len());
+        graph.nodes.push(NodeData {
+            id,
+            page_title: scene_page.clone(),
+            short_description: None,
+            wikitext_description: None,
+            lead_image: None,
+            label: GenreName(
+                scene_page
+                    .heading
+                    .clone()
+                    .unwrap_or_else(|| scene_page.name.clone()),
+            ),
+            aliases: vec![],
+            native_names: vec![],
+            last_revision_date: None,
+            scene: true,
+            stub: false,
+            kind: node_kinds::NodeKind::Scene,
+            advisory: None,
+            media_suppressed: false,
+            tags: BTreeSet::new(),
+            tempo: None,
+            time_signatures: vec![],
+            quality: None,
+            assessment_class: None,
+            importance: None,
+            mixes: None,
+            samples: vec![],
+            references: vec![],
+            description_references: vec![],
+            edges: BTreeSet::new(),
+            relations: BTreeSet::new(),
+            fusions: BTreeSet::new(),
+            navbox_groups: BTreeSet::new(),
+            prose_edges: BTreeSet::new(),
+        });
+        page_to_id.insert(scene_page, id);
+        scene_link_to_id.insert(link, id);
+    }
+
+    // Names and aliases of every genre, for matching the unlinked names in infoboxes.
+    // Names shared by more than one genre are ambiguous, so they're not matched.
+    let mut name_to_id: HashMap<String, Option<PageDataId>> = HashMap::new();
+    for node in graph.nodes.iter().filter(|n| !n.scene) {
+        for name in std::iter::once(&node.label.0).chain(&node.aliases) {
+            name_to_id
+                .entry(name.to_lowercase())
+                .and_modify(|id| {
+                    if *id != Some(node.id) {
+                        *id = None;
+                    }
+                })
+                .or_insert(Some(node.id));
+        }
+    }
+
+    // Third pass: create edges
+    let mut edges = Edges::default();
+    // Subgenre edges from a genre under a heading to its page, which may be inferred
+    let mut heading_edges = vec![];
     for page in &node_order {
         let processed_genre = &processed_genres.0[page];
         let genre_id = *page_to_id.get(page).with_context(|| {
@@ -1489,7 +2550,7 @@ fn produce_data_json(
             })?))
         }
 
-        for stylistic_origin in &processed_genre.stylistic_origins {
+        for (position, stylistic_origin) in processed_genre.stylistic_origins.iter().enumerate() {
             if let Some(source_id) = get_id_for_page(
                 links_to_articles,
                 &page_to_id,
@@ -1497,14 +2558,21 @@ fn produce_data_json(
                 "stylistic origin",
                 stylistic_origin,
             )? {
-                graph.edges.insert(EdgeData {
-                    source: source_id,
-                    target: genre_id,
-                    ty: EdgeType::Derivative,
-                });
+                edges
+                    .insert(source_id, genre_id, EdgeType::Derivative)
+                    .add_provenance(genre_id, "stylistic_origins", position)
+                    .references
+                    .extend(
+                        processed_genre
+                            .link_references_for("stylistic_origins", stylistic_origin)
+                            .map(|reference| EdgeReference {
+                                page: genre_id,
+                                reference,
+                            }),
+                    );
             }
         }
-        for derivative in &processed_genre.derivatives {
+        for (position, derivative) in processed_genre.derivatives.iter().enumerate() {
             if let Some(target_id) = get_id_for_page(
                 links_to_articles,
                 &page_to_id,
@@ -1512,14 +2580,21 @@ fn produce_data_json(
                 "derivative",
                 derivative,
             )? {
-                graph.edges.insert(EdgeData {
-                    source: genre_id,
-                    target: target_id,
-                    ty: EdgeType::Derivative,
-                });
+                edges
+                    .insert(genre_id, target_id, EdgeType::Derivative)
+                    .add_provenance(genre_id, "derivatives", position)
+                    .references
+                    .extend(
+                        processed_genre
+                            .link_references_for("derivatives", derivative)
+                            .map(|reference| EdgeReference {
+                                page: genre_id,
+                                reference,
+                            }),
+                    );
             }
         }
-        for subgenre in &processed_genre.subgenres {
+        for (position, subgenre) in processed_genre.subgenres.iter().enumerate() {
             if let Some(target_id) = get_id_for_page(
                 links_to_articles,
                 &page_to_id,
@@ -1527,14 +2602,21 @@ fn produce_data_json(
                 "subgenre",
                 subgenre,
             )? {
-                graph.edges.insert(EdgeData {
-                    source: genre_id,
-                    target: target_id,
-                    ty: EdgeType::Subgenre,
-                });
+                edges
+                    .insert(genre_id, target_id, EdgeType::Subgenre)
+                    .add_provenance(genre_id, "subgenres", position)
+                    .references
+                    .extend(
+                        processed_genre
+                            .link_references_for("subgenres", subgenre)
+                            .map(|reference| EdgeReference {
+                                page: genre_id,
+                                reference,
+                            }),
+                    );
             }
         }
-        for fusion_genre in &processed_genre.fusion_genres {
+        for (position, fusion_genre) in processed_genre.fusion_genres.iter().enumerate() {
             if let Some(target_id) = get_id_for_page(
                 links_to_articles,
                 &page_to_id,
@@ -1542,10 +2624,88 @@ fn produce_data_json(
                 "fusion genre",
                 fusion_genre,
             )? {
-                graph.edges.insert(EdgeData {
+                edges
+                    .insert(genre_id, target_id, EdgeType::FusionGenre)
+                    .add_provenance(genre_id, "fusiongenres", position)
+                    .references
+                    .extend(
+                        processed_genre
+                            .link_references_for("fusiongenres", fusion_genre)
+                            .map(|reference| EdgeReference {
+                                page: genre_id,
+                                reference,
+                            }),
+                    );
+            }
+        }
+        for (position, unlinked_name) in processed_genre.unlinked_names.iter().enumerate() {
+            let Some(other_id) = links_to_articles
+                .map(&unlinked_name.name)
+                .and_then(|page| page_to_id.get(&page).copied())
+                .or_else(|| {
+                    name_to_id
+                        .get(&unlinked_name.name.to_lowercase())
+                        .copied()
+                        .flatten()
+                })
+                .filter(|id| *id != genre_id)
+            else {
+                continue;
+            };
+            let (source, target, ty) = match unlinked_name.field.as_str() {
+                "stylistic_origins" => (other_id, genre_id, EdgeType::Derivative),
+                "derivatives" => (genre_id, other_id, EdgeType::Derivative),
+                "subgenres" => (genre_id, other_id, EdgeType::Subgenre),
+                "fusiongenres" => (genre_id, other_id, EdgeType::FusionGenre),
+                _ => continue,
+            };
+            edges
+                .insert(source, target, ty)
+                .add_text_matched_provenance(genre_id, &unlinked_name.field, position);
+        }
+        for (field, position, scene) in processed_genre
+            .regional_scenes
+            .iter()
+            .enumerate()
+            .map(|(i, s)| ("regional_scenes", i, s))
+            .chain(
+                processed_genre
+                    .local_scenes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| ("local_scenes", i, s)),
+            )
+        {
+            let scene_id = match get_id_for_page(
+                links_to_articles,
+                &page_to_id,
+                processed_genre,
+                "scene",
+                scene,
+            )? {
+                Some(scene_id) => scene_id,
+                None => scene_link_to_id[&scene.to_lowercase()],
+            };
+            if scene_id != genre_id {
+                edges
+                    .insert(genre_id, scene_id, EdgeType::Scene)
+                    .add_provenance(genre_id, field, position);
+            }
+        }
+        for hatnote in &processed_genre.hatnotes {
+            if let Some(target_id) = get_id_for_page(
+                links_to_articles,
+                &page_to_id,
+                processed_genre,
+                "hatnote",
+                &hatnote.target,
+            )?
+            .filter(|id| *id != genre_id)
+            {
+                graph.relations.insert(RelationData {
                     source: genre_id,
                     target: target_id,
-                    ty: EdgeType::FusionGenre,
+                    ty: hatnote.ty,
                 });
             }
         }
@@ -1558,31 +2718,233 @@ fn produce_data_json(
                 .copied()
                 .filter(|pp| *pp != genre_id)
             {
-                graph.edges.insert(EdgeData {
-                    source: parent_page,
-                    target: genre_id,
-                    ty: EdgeType::Subgenre,
+                edges.insert(parent_page, genre_id, EdgeType::Subgenre);
+                heading_edges.push((parent_page, genre_id, EdgeType::Subgenre));
+            }
+        }
+    }
+
+    // Lists in "Subgenres" sections and list articles are a less reliable source of subgenres.
+    // Entries without an explicit parent belong to the genre whose section they're in, or,
+    // in a list article, to the genre named by their section's heading.
+    let resolve_link = |link: &str| {
+        links_to_articles
+            .map(link)
+            .and_then(|page| page_to_id.get(&page).copied())
+    };
+    let listed_subgenres = node_order
+        .iter()
+        .flat_map(|page| {
+            let genre_id = page_to_id[page];
+            processed_genres.0[page]
+                .listed_subgenres
+                .iter()
+                .map(move |listed| (page, listed, Some(genre_id)))
+        })
+        .chain(list_articles.iter().flat_map(|la| {
+            la.genres.iter().map(move |listed| {
+                let section_id = listed.section.as_deref().and_then(|section| {
+                    resolve_link(section).or_else(|| resolve_link(&format!("{section} music")))
+                });
+                (&la.page, listed, section_id)
+            })
+        }));
+    for (page, listed, implied_parent_id) in listed_subgenres {
+        let parent_id = match &listed.parent {
+            Some(parent) => resolve_link(parent),
+            None => implied_parent_id,
+        };
+        let (Some(parent_id), Some(child_id)) = (parent_id, resolve_link(&listed.link)) else {
+            continue;
+        };
+        if parent_id != child_id {
+            edges
+                .insert(parent_id, child_id, EdgeType::Subgenre)
+                .add_listing(page, listed.section.as_deref());
+        }
+    }
+
+    // Edges from headings are only inferred if neither an infobox nor a list described them,
+    // so this has to wait until every other source has been added
+    for key in heading_edges {
+        let edge = edges.0.get_mut(&key).unwrap();
+        edge.inferred = edge.provenance.is_empty() && edge.listings.is_empty();
+    }
+
+    // Group the parents of each fusion genre into a single hyperedge. A fusion's own
+    // stylistic origins are included, as the fusion may only be listed by one of its parents.
+    let mut fusion_members: BTreeMap<PageDataId, BTreeSet<PageDataId>> = BTreeMap::new();
+    for edge in edges.0.values().filter(|e| e.ty == EdgeType::FusionGenre) {
+        fusion_members
+            .entry(edge.target)
+            .or_default()
+            .insert(edge.source);
+    }
+    for edge in edges.0.values().filter(|e| e.ty == EdgeType::Derivative) {
+        let Some(members) = fusion_members.get_mut(&edge.target) else {
+            continue;
+        };
+        if edge
+            .provenance
+            .iter()
+            .any(|p| p.page == edge.target && p.field == "stylistic_origins")
+        {
+            members.insert(edge.source);
+        }
+    }
+    // A fusion of a single genre isn't much of a fusion
+    graph.fusions = fusion_members
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(fusion, members)| FusionData { fusion, members })
+        .collect();
+
+    // As with fusions, a group with a single genre doesn't cluster anything
+    for navbox in navboxes {
+        for group in &navbox.groups {
+            let members = group
+                .links
+                .iter()
+                .filter_map(|link| links_to_articles.map(link))
+                .filter_map(|page| page_to_id.get(&page).copied())
+                .collect::<BTreeSet<_>>();
+            if members.len() > 1 {
+                graph.navbox_groups.push(NavboxGroupData {
+                    navbox: navbox.page.clone(),
+                    title: navbox.title.clone(),
+                    group: group.name.clone(),
+                    members,
                 });
             }
         }
     }
 
-    // Third pass (over edges): update inbound/outbound sets
+    graph.edges = edges.0.into_values().collect();
+
+    // Find sentences in the articles of either end of each edge that link to the other end
+    let mut article_sentences: HashMap<&str, Vec<(String, BTreeSet<PageDataId>)>> = HashMap::new();
+    for (article, path) in genres.iter() {
+        let wikitext = std::fs::read_to_string(path)?;
+        let Some((_header, wikitext)) = wikitext.split_once('\n') else {
+            continue;
+        };
+        // Commented-out wikitext isn't part of the article, and its links aren't evidence
+        let wikitext = prose::strip_refs_and_comments(wikitext);
+        let sentences = prose::sentences_and_lines(&wikitext)
+            .into_iter()
+            // Skip tables, templates, headings and lists, which aren't prose
+            .filter(|sentence| !sentence.starts_with(['|', '!', '{', '=', '*', '#', ':', ';']))
+            .filter_map(|sentence| {
+                let (_, links) = prose::plain_text_and_links(sentence);
+                let ids = links
+                    .iter()
+                    .filter_map(|link| links_to_articles.map(&link.target))
+                    .filter_map(|page| page_to_id.get(&page).copied())
+                    .collect::<BTreeSet<_>>();
+                (!ids.is_empty()).then(|| (sentence.trim().to_string(), ids))
+            })
+            .collect();
+        article_sentences.insert(article.name.as_str(), sentences);
+    }
+    for edge in &mut graph.edges {
+        for (page, other) in [(edge.source, edge.target), (edge.target, edge.source)] {
+            let Some(sentences) =
+                article_sentences.get(graph.nodes[page.0].page_title.name.as_str())
+            else {
+                continue;
+            };
+            let remaining = MAX_EDGE_EVIDENCE.saturating_sub(edge.evidence.len());
+            edge.evidence.extend(
+                sentences
+                    .iter()
+                    .filter(|(_, ids)| ids.contains(&other))
+                    .take(remaining)
+                    .map(|(sentence, _)| EdgeEvidence {
+                        page,
+                        sentence: sentence.clone(),
+                    }),
+            );
+        }
+    }
+
+    // Candidate edges from the descriptions, keeping the most confident candidate for each
+    let mut prose_edges: BTreeMap<(PageDataId, PageDataId, EdgeType), ProseEdgeData> =
+        BTreeMap::new();
+    for page in &node_order {
+        let processed_genre = &processed_genres.0[page];
+        let genre_id = page_to_id[page];
+        for relation in &processed_genre.prose_relations {
+            let Some(other_id) = links_to_articles
+                .map(&relation.link)
+                .and_then(|page| page_to_id.get(&page).copied())
+                .filter(|id| *id != genre_id)
+            else {
+                continue;
+            };
+            let (source, target, ty) = match relation.ty {
+                prose::RelationType::SubgenreOf => (other_id, genre_id, EdgeType::Subgenre),
+                prose::RelationType::FusionOf => (other_id, genre_id, EdgeType::FusionGenre),
+                prose::RelationType::DerivedFrom => (other_id, genre_id, EdgeType::Derivative),
+                prose::RelationType::HasSubgenre => (genre_id, other_id, EdgeType::Subgenre),
+                prose::RelationType::GaveRiseTo => (genre_id, other_id, EdgeType::Derivative),
+            };
+            let candidate = ProseEdgeData {
+                source,
+                target,
+                ty,
+                page: genre_id,
+                sentence: relation.sentence.clone(),
+                confidence: relation.confidence,
+            };
+            match prose_edges.entry((source, target, ty)) {
+                std::collections::btree_map::Entry::Vacant(entry) => {
+                    entry.insert(candidate);
+                }
+                std::collections::btree_map::Entry::Occupied(mut entry) => {
+                    if entry.get().confidence < candidate.confidence {
+                        entry.insert(candidate);
+                    }
+                }
+            }
+        }
+    }
+    graph.prose_edges = prose_edges.into_values().collect();
+
+    // Fourth pass (over edges): update inbound/outbound sets
     for (i, edge) in graph.edges.iter().enumerate() {
         graph.nodes[edge.source.0].edges.insert(i);
         graph.nodes[edge.target.0].edges.insert(i);
     }
+    for (i, relation) in graph.relations.iter().enumerate() {
+        graph.nodes[relation.source.0].relations.insert(i);
+        graph.nodes[relation.target.0].relations.insert(i);
+    }
+    for (i, prose_edge) in graph.prose_edges.iter().enumerate() {
+        graph.nodes[prose_edge.source.0].prose_edges.insert(i);
+        graph.nodes[prose_edge.target.0].prose_edges.insert(i);
+    }
+    for (i, fusion) in graph.fusions.iter().enumerate() {
+        for id in std::iter::once(&fusion.fusion).chain(&fusion.members) {
+            graph.nodes[id.0].fusions.insert(i);
+        }
+    }
+    for (i, navbox_group) in graph.navbox_groups.iter().enumerate() {
+        for id in &navbox_group.members {
+            graph.nodes[id.0].navbox_groups.insert(i);
+        }
+    }
 
-    // Fourth pass: calculate max degree
+    // Fifth pass: calculate max degree
     graph.max_degree = graph.nodes.iter().map(|n| n.edges.len()).max().unwrap_or(0);
 
-    // Fifth pass (over links_to_articles): update links_to_page_ids
+    // Sixth pass (over links_to_articles and scenes): update links_to_page_ids
     graph.links_to_page_ids.extend(
         links_to_articles
             .0
             .iter()
             .filter_map(|(link, page)| page_to_id.get(page).map(|id| (link.clone(), *id))),
     );
+    graph.links_to_page_ids.extend(scene_link_to_id);
 
     std::fs::write(data_path, serde_json::to_string_pretty(&graph)?)?;
     println!("{:.2}s: Saved data.json", start.elapsed().as_secs_f32());
@@ -1590,6 +2952,23 @@ fn produce_data_json(
     Ok(())
 }
 
+/// Converts a link to a scene into the page name it refers to.
+///
+/// Wikipedia capitalises the first letter of page names, so we do the same to ensure
+/// that `[[detroit techno]]` and `[[Detroit techno]]` refer to the same scene.
+fn scene_page_name(link: &str) -> PageName {
+    let (name, heading) = match link.split_once('#') {
+        Some((name, heading)) => (name, Some(heading.to_string())),
+        None => (link, None),
+    };
+    let mut chars = name.trim().chars();
+    let name = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+        None => String::new(),
+    };
+    PageName::new(name.replace('_', " "), heading)
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
 enum GenreMix {
@@ -1744,6 +3123,205 @@ fn get_links_from_nodes(nodes: &[pwt::Node]) -> Vec<String> {
     output
 }
 
+/// Like [`get_links_from_nodes`], but also records the citations that follow each link.
+fn get_links_and_references_from_nodes(
+    pwt_configuration: &pwt::Configuration,
+    wikitext: &str,
+    nodes: &[pwt::Node],
+    named_refs: &HashMap<String, String>,
+    field: &str,
+    references: &mut Vec<citations::Citation>,
+    link_references: &mut Vec<LinkReferences>,
+) -> Vec<String> {
+    enum Item {
+        Link(String),
+        Ref(usize),
+    }
+    let mut items = vec![];
+    nodes_recurse(nodes, &mut items, |items, node| match node {
+        pwt::Node::Link { target, .. } => {
+            items.push(Item::Link(target.to_string()));
+            false
+        }
+        pwt::Node::Tag { name, start, .. } | pwt::Node::StartTag { name, start, .. }
+            if name == "ref" =>
+        {
+            items.push(Item::Ref(*start));
+            false
+        }
+        _ => true,
+    });
+
+    let mut links: Vec<String> = vec![];
+    for item in items {
+        match item {
+            Item::Link(link) => links.push(link),
+            Item::Ref(start) => {
+                // Citations are attributed to the link that precedes them
+                let Some(link) = links.last() else {
+                    continue;
+                };
+                let Some(citation) = citations::Citation::from_ref_at(
+                    pwt_configuration,
+                    wikitext,
+                    start,
+                    named_refs,
+                ) else {
+                    continue;
+                };
+                let index = citations::add_reference(references, citation);
+                match link_references
+                    .iter_mut()
+                    .find(|lr| lr.field == field && &lr.link == link)
+                {
+                    Some(lr) if !lr.references.contains(&index) => lr.references.push(index),
+                    Some(_) => {}
+                    None => link_references.push(LinkReferences {
+                        field: field.to_string(),
+                        link: link.clone(),
+                        references: vec![index],
+                    }),
+                }
+            }
+        }
+    }
+    links
+}
+
+fn is_br(node: &pwt::Node) -> bool {
+    matches!(node, pwt::Node::StartTag { name, .. } if name == "br")
+}
+
+/// Extracts a list of names from nodes, where the names may be separated by `<br>`,
+/// commas, semicolons, lists or list templates like `{{hlist}}`.
+fn get_names_from_nodes(nodes: &[pwt::Node]) -> Vec<String> {
+    names_from_nodes(nodes, false)
+}
+
+/// Like [`get_names_from_nodes`], but skips any name that contains a link, as those are
+/// already picked up by [`get_links_from_nodes`].
+fn get_unlinked_names_from_nodes(nodes: &[pwt::Node]) -> Vec<String> {
+    names_from_nodes(nodes, true)
+}
+
+fn names_from_nodes(nodes: &[pwt::Node], unlinked_only: bool) -> Vec<String> {
+    fn is_list_template(template_name: &str) -> bool {
+        [
+            "hlist",
+            "flatlist",
+            "plainlist",
+            "ubl",
+            "ubil",
+            "unbulleted list",
+            "bulleted list",
+        ]
+        .contains(&template_name)
+    }
+
+    // Takes the name built up so far, unless it's being skipped for containing a link
+    fn take_name(current: &mut String, linked: &mut bool, unlinked_only: bool) -> Option<String> {
+        let name = std::mem::take(current);
+        if std::mem::take(linked) && unlinked_only {
+            return None;
+        }
+        clean_name(&name)
+    }
+
+    let mut names = vec![];
+    for segment in nodes.split(is_br) {
+        let mut current = String::new();
+        let mut linked = false;
+        for node in segment {
+            match node {
+                pwt::Node::Template {
+                    name, parameters, ..
+                } if is_list_template(
+                    &nodes_inner_text(name, &InnerTextConfig::default()).to_lowercase(),
+                ) =>
+                {
+                    names.extend(take_name(&mut current, &mut linked, unlinked_only));
+                    for parameter in parameters.iter().filter(|p| p.name.is_none()) {
+                        names.extend(names_from_nodes(&parameter.value, unlinked_only));
+                    }
+                }
+                pwt::Node::OrderedList { items, .. } | pwt::Node::UnorderedList { items, .. } => {
+                    names.extend(take_name(&mut current, &mut linked, unlinked_only));
+                    for item in items {
+                        names.extend(names_from_nodes(&item.nodes, unlinked_only));
+                    }
+                }
+                pwt::Node::Text { value, .. } => {
+                    let mut parts = value.split([',', ';']);
+                    current.push_str(parts.next().unwrap_or_default());
+                    for part in parts {
+                        names.extend(take_name(&mut current, &mut linked, unlinked_only));
+                        current.push_str(part);
+                    }
+                }
+                pwt::Node::Link { .. } => {
+                    linked = true;
+                    current.push_str(&node_inner_text(node, &InnerTextConfig::default()));
+                }
+                _ => current.push_str(&node_inner_text(node, &InnerTextConfig::default())),
+            }
+        }
+        names.extend(take_name(&mut current, &mut linked, unlinked_only));
+    }
+    names
+}
+
+/// Trims whitespace and enclosing brackets or quotes from a name, returning `None` if nothing is left.
+fn clean_name(name: &str) -> Option<String> {
+    let mut name = name.trim();
+    for (open, close) in [('(', ')'), ('"', '"'), ('\'', '\'')] {
+        if let Some(inner) = name
+            .strip_prefix(open)
+            .and_then(|name| name.strip_suffix(close))
+        {
+            name = inner.trim();
+        }
+    }
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod name_tests {
+    use super::*;
+
+    fn names(wikitext: &str) -> Vec<String> {
+        get_names_from_nodes(&pwt_configuration().parse(wikitext).unwrap().nodes)
+    }
+
+    #[test]
+    fn test_names_separated_by_br_and_commas() {
+        assert_eq!(
+            names("DnB, D'n'B<br>(Jungle)<br>Jungle (in part)"),
+            vec!["DnB", "D'n'B", "Jungle", "Jungle (in part)"]
+        );
+    }
+
+    #[test]
+    fn test_names_in_list_template() {
+        assert_eq!(
+            names("{{hlist|''Sertanejo universitário''|[[Música caipira]]}}"),
+            vec!["Sertanejo universitário", "Música caipira"]
+        );
+    }
+
+    #[test]
+    fn test_unlinked_names() {
+        assert_eq!(
+            get_unlinked_names_from_nodes(
+                &pwt_configuration()
+                    .parse("Go-go, [[Funk]]<br>Soul ([[Motown]])<br>{{hlist|Boogie|[[Jazz]]}}")
+                    .unwrap()
+                    .nodes
+            ),
+            vec!["Go-go", "Boogie"]
+        );
+    }
+}
+
 fn nodes_recurse<R>(
     nodes: &[pwt::Node],
     result: &mut R,
@@ -1840,6 +3418,22 @@ fn parameters_to_map<'a>(
         .collect()
 }
 
+#[cfg(test)]
+/// Calls `on_template` with the lowercase name and parameters of every top-level template in
+/// `wikitext`, as [`process_genres`] does, for testing the extractors it calls.
+fn for_each_template(wikitext: &str, mut on_template: impl FnMut(&str, &[pwt::Parameter])) {
+    let parsed_wikitext = pwt_configuration().parse(wikitext).unwrap();
+    for node in &parsed_wikitext.nodes {
+        if let pwt::Node::Template {
+            name, parameters, ..
+        } = node
+        {
+            let template_name = nodes_inner_text(name, &InnerTextConfig::default()).to_lowercase();
+            on_template(&template_name, parameters);
+        }
+    }
+}
+
 /// Makes a Wikipedia page name safe to store on disk.
 fn sanitize_page_name(title: &PageName) -> String {
     // We use BIG SOLIDUS (⧸) as it's unlikely to be used in a page name
@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prose;
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// The range of tempos a genre is described as having, in beats per minute. A single
+/// tempo (i.e. "around 140 BPM") has the same minimum and maximum.
+pub struct TempoRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimeSignature {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+/// Tempos outside of this range are assumed to be other numbers that happen to precede "BPM".
+const TEMPO_RANGE: std::ops::RangeInclusive<u32> = 20..=1000;
+
+/// Words that follow a time signature in prose (i.e. "in 7/8 time").
+const METER_WORDS: &[&str] = &[
+    "time",
+    "meter",
+    "metre",
+    "signature",
+    "rhythm",
+    "beat",
+    "feel",
+];
+
+/// The tempo range stated in a description. If several tempos are mentioned, the range
+/// covers all of them.
+pub fn tempo(description: &str) -> Option<TempoRange> {
+    let mut range: Option<TempoRange> = None;
+    for sentence in prose::sentences(description) {
+        let text = plain_text(sentence);
+        for unit in ["bpm", "beats per minute"] {
+            for (index, _) in text.match_indices(unit) {
+                let is_word = !text[index + unit.len()..].starts_with(char::is_alphanumeric);
+                let Some(new) = tempo_before(&text[..index]).filter(|_| is_word) else {
+                    continue;
+                };
+                range = Some(match range {
+                    Some(range) => TempoRange {
+                        min: range.min.min(new.min),
+                        max: range.max.max(new.max),
+                    },
+                    None => new,
+                });
+            }
+        }
+    }
+    range
+}
+
+/// The time signatures in a description, from `{{music|time|4|4}}` templates and from prose
+/// (i.e. "in 12/8 time"), in the order they're first mentioned.
+pub fn time_signatures(description: &str) -> Vec<TimeSignature> {
+    let mut time_signatures = vec![];
+    let mut push = |time_signature: TimeSignature| {
+        if !time_signatures.contains(&time_signature) {
+            time_signatures.push(time_signature);
+        }
+    };
+
+    for rest in description.split("{{").skip(1) {
+        let Some(end) = rest.find("}}") else {
+            continue;
+        };
+        let parameters = rest[..end].split('|').map(str::trim).collect::<Vec<_>>();
+        if let ["music" | "Music", "time", rest @ ..] = parameters.as_slice() {
+            // The template defaults to common time, as `TimeSignature.tsx` does
+            let numerator = rest.first().copied().unwrap_or("4");
+            let denominator = rest.get(1).copied().unwrap_or("4");
+            if let Some(time_signature) = parse_time_signature(numerator, denominator) {
+                push(time_signature);
+            }
+        }
+    }
+
+    for sentence in prose::sentences(description) {
+        let text = plain_text(sentence);
+        for (index, _) in text.match_indices('/') {
+            let before = &text[..index];
+            let after = &text[index + 1..];
+            let numerator = &before[before.trim_end_matches(|c: char| c.is_ascii_digit()).len()..];
+            let denominator = &after[..after
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(after.len())];
+            let following = after[denominator.len()..].trim_start();
+            let is_meter = METER_WORDS.iter().any(|word| following.starts_with(word))
+                || before.trim_end().ends_with("time signature of");
+            if !is_meter {
+                continue;
+            }
+            if let Some(time_signature) = parse_time_signature(numerator, denominator) {
+                push(time_signature);
+            }
+        }
+    }
+
+    time_signatures
+}
+
+/// Lowercase plain text for a sentence of wikitext, with spaces and dashes normalised.
+fn plain_text(sentence: &str) -> String {
+    let (plain, _) = prose::plain_text_and_links(sentence);
+    plain
+        .to_lowercase()
+        .replace("&nbsp;", " ")
+        .replace("&ndash;", "-")
+        .replace("&mdash;", "-")
+        .replace(['–', '—'], "-")
+}
+
+/// The tempo or tempo range at the end of `text` (i.e. "120-135 ", "between 120 and 135 ",
+/// "120 bpm to 135 ").
+fn tempo_before(text: &str) -> Option<TempoRange> {
+    let (rest, max) = trailing_number(text)?;
+    let rest = rest.trim_end();
+    let separator = ["-", " to", " and"]
+        .into_iter()
+        .find_map(|separator| rest.strip_suffix(separator));
+    let min = separator
+        .map(|rest| {
+            let rest = rest.trim_end();
+            rest.strip_suffix("bpm").unwrap_or(rest)
+        })
+        .and_then(trailing_number)
+        .map(|(_, min)| min)
+        .filter(|min| *min <= max)
+        .unwrap_or(max);
+    (TEMPO_RANGE.contains(&min) && TEMPO_RANGE.contains(&max)).then_some(TempoRange { min, max })
+}
+
+/// Splits a number off the end of `text`, ignoring trailing whitespace.
+fn trailing_number(text: &str) -> Option<(&str, u32)> {
+    let text = text.trim_end();
+    let start = text.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    Some((&text[..start], text[start..].parse().ok()?))
+}
+
+fn parse_time_signature(numerator: &str, denominator: &str) -> Option<TimeSignature> {
+    let numerator = numerator.parse().ok().filter(|n| (1..=32).contains(n))?;
+    let denominator = denominator
+        .parse()
+        .ok()
+        .filter(|d| [1, 2, 4, 8, 16, 32].contains(d))?;
+    Some(TimeSignature {
+        numerator,
+        denominator,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tempo() {
+        assert_eq!(
+            tempo("'''Trance''' is typically between 135 and 150&nbsp;BPM.<ref>X</ref>"),
+            Some(TempoRange { min: 135, max: 150 })
+        );
+        assert_eq!(
+            tempo("It has a tempo of 120&ndash;135 [[Tempo|BPM]], sometimes reaching 140 bpm."),
+            Some(TempoRange { min: 120, max: 140 })
+        );
+        assert_eq!(
+            tempo("Tempos range from 160 to 180 beats per minute."),
+            Some(TempoRange { min: 160, max: 180 })
+        );
+        assert_eq!(tempo("It emerged in the 1990s."), None);
+        assert_eq!(
+            tempo("It is usually played at ≈140 BPM."),
+            Some(TempoRange { min: 140, max: 140 })
+        );
+    }
+
+    #[test]
+    fn test_time_signatures() {
+        assert_eq!(
+            time_signatures(
+                "It is played in {{music|time|12|8}}, with some songs in 7/8 time. It emerged in 1990/91."
+            ),
+            vec![
+                TimeSignature {
+                    numerator: 12,
+                    denominator: 8
+                },
+                TimeSignature {
+                    numerator: 7,
+                    denominator: 8
+                },
+            ]
+        );
+        assert_eq!(
+            time_signatures("Its name means “café/bar”, and songs are in “5/4 time”."),
+            vec![TimeSignature {
+                numerator: 5,
+                denominator: 4
+            }]
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+
+use parse_wiki_text_2 as pwt;
+use serde::{Deserialize, Serialize};
+use wikitext_util::{nodes_inner_text, InnerTextConfig};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+/// A name for a genre in its native language, as given by a language template in the lead.
+pub struct NativeName {
+    pub text: String,
+    /// The IETF language tag for the name (i.e. `ja`, `zh-Hant`).
+    pub tag: String,
+    /// The ISO 15924 script code for the name (i.e. `Jpan`, `Cyrl`), if it could be determined.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transliteration: Option<String>,
+}
+impl NativeName {
+    fn new(tag: &str, text: &str, transliteration: Option<String>) -> Option<Self> {
+        let tag = tag.trim();
+        let text = text.trim();
+        if tag.is_empty() || text.is_empty() {
+            return None;
+        }
+        Some(Self {
+            text: text.to_string(),
+            tag: tag.to_string(),
+            script: script_for(tag, text).map(|s| s.to_string()),
+            transliteration: transliteration
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty()),
+        })
+    }
+}
+
+/// Extracts native names from a language template (`{{lang}}`, `{{langx}}`, `{{lang-xx}}`,
+/// `{{nihongo}}`, `{{zh}}`) and adds them to `native_names`.
+///
+/// Transliteration templates (`{{transl}}`) don't carry a native name, so their transliteration
+/// is attached to the last name in the same language that doesn't have one yet.
+pub fn extract_from_template(
+    template_name: &str,
+    parameters: &[pwt::Parameter],
+    native_names: &mut Vec<NativeName>,
+) {
+    let (positional, named) = split_parameters(parameters);
+    let positional_arg = |i: usize| positional.get(i).map(String::as_str).unwrap_or_default();
+    let named_arg = |name: &str| named.get(name).filter(|s| !s.is_empty()).cloned();
+
+    let new_names: Vec<NativeName> = match template_name {
+        "lang" => NativeName::new(
+            positional_arg(0),
+            &named_arg("text").unwrap_or_else(|| positional_arg(1).to_string()),
+            None,
+        )
+        .into_iter()
+        .collect(),
+        "langx" => NativeName::new(
+            positional_arg(0),
+            &named_arg("text").unwrap_or_else(|| positional_arg(1).to_string()),
+            named_arg("translit").or_else(|| positional.get(2).cloned()),
+        )
+        .into_iter()
+        .collect(),
+        "nihongo" => NativeName::new("ja", positional_arg(1), positional.get(2).cloned())
+            .into_iter()
+            .collect(),
+        "zh" => [("t", "zh-Hant"), ("s", "zh-Hans"), ("c", "zh")]
+            .into_iter()
+            .filter_map(|(parameter, tag)| {
+                NativeName::new(tag, &named_arg(parameter)?, named_arg("p"))
+            })
+            .collect(),
+        "transl" | "transliteration" | "tlit" => {
+            // The text is either the second or the third positional argument; in the case of the
+            // latter, the second argument is the transliteration scheme.
+            let language = positional_arg(0);
+            let transliteration = if positional.len() >= 3 {
+                positional_arg(2)
+            } else {
+                positional_arg(1)
+            };
+            match native_names.iter_mut().rev().find(|n| {
+                n.transliteration.is_none()
+                    && primary_language(&n.tag) == primary_language(language)
+            }) {
+                Some(name) if !transliteration.trim().is_empty() => {
+                    name.transliteration = Some(transliteration.trim().to_string());
+                    vec![]
+                }
+                Some(_) => vec![],
+                None => NativeName::new(&format!("{language}-Latn"), transliteration, None)
+                    .into_iter()
+                    .collect(),
+            }
+        }
+        other => other
+            .strip_prefix("lang-")
+            .and_then(|tag| NativeName::new(tag, positional_arg(0), named_arg("translit")))
+            .into_iter()
+            .collect(),
+    };
+
+    for name in new_names {
+        if !native_names.contains(&name) {
+            native_names.push(name);
+        }
+    }
+}
+
+fn split_parameters(parameters: &[pwt::Parameter]) -> (Vec<String>, HashMap<String, String>) {
+    let mut positional = vec![];
+    let mut named = HashMap::new();
+    for parameter in parameters {
+        let value = nodes_inner_text(&parameter.value, &InnerTextConfig::default());
+        match &parameter.name {
+            Some(name) => {
+                named.insert(nodes_inner_text(name, &InnerTextConfig::default()), value);
+            }
+            None => positional.push(value),
+        }
+    }
+    (positional, named)
+}
+
+fn primary_language(tag: &str) -> String {
+    tag.split('-').next().unwrap_or_default().to_lowercase()
+}
+
+/// Determines the script of `text`, preferring an explicit script subtag in `tag` (i.e. `sr-Cyrl`)
+/// and otherwise guessing from the characters used.
+fn script_for(tag: &str, text: &str) -> Option<&'static str> {
+    const SCRIPTS: &[&str] = &[
+        "Arab", "Armn", "Beng", "Cyrl", "Deva", "Geor", "Grek", "Hang", "Hani", "Hans", "Hant",
+        "Hebr", "Jpan", "Kore", "Latn", "Thai",
+    ];
+    if let Some(script) = tag.split('-').skip(1).find_map(|subtag| {
+        SCRIPTS
+            .iter()
+            .find(|script| script.eq_ignore_ascii_case(subtag))
+            .copied()
+    }) {
+        return Some(script);
+    }
+
+    // Kana can only be Japanese, but Han characters are shared between Chinese and Japanese,
+    // so we check for the former before falling back to the first script we recognise.
+    if text
+        .chars()
+        .any(|c| matches!(c, '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}'))
+    {
+        return Some("Jpan");
+    }
+    text.chars().find_map(|c| match c {
+        'A'..='Z' | 'a'..='z' | '\u{00C0}'..='\u{024F}' => Some("Latn"),
+        '\u{0370}'..='\u{03FF}' => Some("Grek"),
+        '\u{0400}'..='\u{04FF}' => Some("Cyrl"),
+        '\u{0530}'..='\u{058F}' => Some("Armn"),
+        '\u{0590}'..='\u{05FF}' => Some("Hebr"),
+        '\u{0600}'..='\u{06FF}' => Some("Arab"),
+        '\u{0900}'..='\u{097F}' => Some("Deva"),
+        '\u{0980}'..='\u{09FF}' => Some("Beng"),
+        '\u{0E00}'..='\u{0E7F}' => Some("Thai"),
+        '\u{10A0}'..='\u{10FF}' => Some("Geor"),
+        '\u{1100}'..='\u{11FF}' | '\u{AC00}'..='\u{D7AF}' => Some("Hang"),
+        '\u{4E00}'..='\u{9FFF}' => Some("Hani"),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn native_names(wikitext: &str) -> Vec<NativeName> {
+        let mut native_names = vec![];
+        crate::for_each_template(wikitext, |template_name, parameters| {
+            extract_from_template(template_name, parameters, &mut native_names)
+        });
+        native_names
+    }
+
+    #[test]
+    fn test_nihongo() {
+        assert_eq!(
+            native_names("'''City pop''' ({{nihongo|City pop|シティ・ポップ|shiti poppu}})"),
+            vec![NativeName {
+                text: "シティ・ポップ".to_string(),
+                tag: "ja".to_string(),
+                script: Some("Jpan".to_string()),
+                transliteration: Some("shiti poppu".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lang_followed_by_transl() {
+        assert_eq!(
+            native_names("{{lang|ru|Русский рок}}, {{transl|ru|Russkiy rok}}"),
+            vec![NativeName {
+                text: "Русский рок".to_string(),
+                tag: "ru".to_string(),
+                script: Some("Cyrl".to_string()),
+                transliteration: Some("Russkiy rok".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_zh() {
+        assert_eq!(
+            native_names("{{zh|t=華語流行音樂|s=华语流行音乐|p=Huáyǔ liúxíng yīnyuè}}"),
+            vec![
+                NativeName {
+                    text: "華語流行音樂".to_string(),
+                    tag: "zh-Hant".to_string(),
+                    script: Some("Hant".to_string()),
+                    transliteration: Some("Huáyǔ liúxíng yīnyuè".to_string()),
+                },
+                NativeName {
+                    text: "华语流行音乐".to_string(),
+                    tag: "zh-Hans".to_string(),
+                    script: Some("Hans".to_string()),
+                    transliteration: Some("Huáyǔ liúxíng yīnyuè".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_langx_and_lang_xx() {
+        assert_eq!(
+            native_names("{{langx|pt|música sertaneja}} {{lang-el|Ρεμπέτικο|translit=Rebetiko}}"),
+            vec![
+                NativeName {
+                    text: "música sertaneja".to_string(),
+                    tag: "pt".to_string(),
+                    script: Some("Latn".to_string()),
+                    transliteration: None,
+                },
+                NativeName {
+                    text: "Ρεμπέτικο".to_string(),
+                    tag: "el".to_string(),
+                    script: Some("Grek".to_string()),
+                    transliteration: Some("Rebetiko".to_string()),
+                },
+            ]
+        );
+    }
+}
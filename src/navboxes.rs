@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+
+use anyhow::Context;
+use parse_wiki_text_2 as pwt;
+use wikitext_util::{nodes_inner_text, pwt_configuration, InnerTextConfig};
+
+use crate::{get_links_from_nodes, parameters_to_map, GenrePages, PageName, WikitextHeader};
+
+/// A navbox template (i.e. `{{Rock music}}`), which groups genres by era and family.
+pub struct Navbox {
+    /// The template's page (i.e. `Template:Rock music`)
+    pub page: PageName,
+    pub title: String,
+    pub groups: Vec<NavboxGroup>,
+}
+
+/// A single `group`/`list` pair of a navbox. Groups of child navboxes are named after
+/// both the parent group and their own group (i.e. "Subgenres › Hard rock").
+pub struct NavboxGroup {
+    pub name: String,
+    /// Unresolved links
+    pub links: Vec<String>,
+}
+
+/// Whether a page from the dump is a navbox that may group genres. Navboxes that never
+/// mention music are skipped to avoid saving every navbox on Wikipedia.
+pub fn is_navbox(title: &str, text: &str) -> bool {
+    if !title.starts_with("Template:") || title.contains('/') {
+        return false;
+    }
+    let text = text.to_lowercase();
+    text.contains("{{navbox") && text.contains("music")
+}
+
+/// Parses every navbox that is used on at least one genre page.
+pub fn process_navboxes(
+    start: std::time::Instant,
+    navbox_pages: &GenrePages,
+    genres: &GenrePages,
+) -> anyhow::Result<Vec<Navbox>> {
+    let mut used_templates = HashSet::new();
+    for (_, path) in genres.iter() {
+        used_templates.extend(used_template_names(&std::fs::read_to_string(path)?));
+    }
+
+    let pwt_configuration = pwt_configuration();
+    let mut navboxes = vec![];
+    for (page, path) in navbox_pages.iter() {
+        let Some(template_name) = page.name.strip_prefix("Template:") else {
+            continue;
+        };
+        if !used_templates.contains(&normalise_template_name(template_name)) {
+            continue;
+        }
+
+        let wikitext = std::fs::read_to_string(path)?;
+        let (wikitext_header, wikitext) = wikitext
+            .split_once('\n')
+            .with_context(|| format!("Missing header for {page}"))?;
+        let _: WikitextHeader = serde_json::from_str(wikitext_header)?;
+        let Ok(parsed_wikitext) =
+            pwt_configuration.parse_with_timeout(wikitext, std::time::Duration::from_secs(5))
+        else {
+            eprintln!("Failed to parse navbox {page}");
+            continue;
+        };
+        let Some(parameters) = parsed_wikitext
+            .nodes
+            .iter()
+            .find_map(navbox_template_parameters)
+        else {
+            continue;
+        };
+
+        let title = parameters_to_map(parameters)
+            .get("title")
+            .map(|title| nodes_inner_text(title, &InnerTextConfig::default()))
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| template_name.to_string());
+        let mut groups = vec![];
+        groups_from_parameters(parameters, &title, None, &mut groups);
+        navboxes.push(Navbox {
+            page: page.clone(),
+            title,
+            groups,
+        });
+    }
+    navboxes.sort_by(|a, b| a.page.cmp(&b.page));
+
+    println!(
+        "{:.2}s: processed {} navboxes used by genres ({} groups)",
+        start.elapsed().as_secs_f32(),
+        navboxes.len(),
+        navboxes.iter().map(|n| n.groups.len()).sum::<usize>()
+    );
+    Ok(navboxes)
+}
+
+/// The parameters of `node` if it's a `{{Navbox}}` (or one of its variants, like
+/// `{{Navbox with collapsible groups}}` or `{{Navbox subgroup}}`).
+fn navbox_template_parameters<'a>(node: &'a pwt::Node<'a>) -> Option<&'a [pwt::Parameter<'a>]> {
+    match node {
+        pwt::Node::Template {
+            name, parameters, ..
+        } if nodes_inner_text(name, &InnerTextConfig::default())
+            .to_lowercase()
+            .starts_with("navbox") =>
+        {
+            Some(parameters)
+        }
+        _ => None,
+    }
+}
+
+fn groups_from_parameters(
+    parameters: &[pwt::Parameter],
+    title: &str,
+    parent_group: Option<&str>,
+    output: &mut Vec<NavboxGroup>,
+) {
+    let parameters = parameters_to_map(parameters);
+    let mut lists = parameters
+        .iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix("list")?.parse::<u32>().ok()?, *value)))
+        .collect::<Vec<_>>();
+    lists.sort_by_key(|(index, _)| *index);
+
+    for (index, list) in lists {
+        let group = parameters
+            .get(&format!("group{index}"))
+            .map(|group| nodes_inner_text(group, &InnerTextConfig::default()))
+            .filter(|group| !group.is_empty());
+        let name = match (parent_group, group) {
+            (Some(parent_group), Some(group)) => format!("{parent_group} › {group}"),
+            (Some(parent_group), None) => parent_group.to_string(),
+            (None, Some(group)) => group,
+            (None, None) => title.to_string(),
+        };
+
+        // A list can be a child navbox with groups of its own
+        let children = list
+            .iter()
+            .filter_map(navbox_template_parameters)
+            .collect::<Vec<_>>();
+        if children.is_empty() {
+            let links = get_links_from_nodes(list);
+            if !links.is_empty() {
+                output.push(NavboxGroup { name, links });
+            }
+        } else {
+            for child in children {
+                groups_from_parameters(child, title, Some(&name), output);
+            }
+        }
+    }
+}
+
+/// The normalised names of every template transcluded by `wikitext`. This is a textual scan,
+/// as it's only used to find which navboxes are in use.
+fn used_template_names(wikitext: &str) -> HashSet<String> {
+    wikitext
+        .split("{{")
+        .skip(1)
+        .filter_map(|rest| {
+            let end = rest.find(['|', '}', '\n']).unwrap_or(rest.len());
+            let name = normalise_template_name(&rest[..end]);
+            (!name.is_empty()).then_some(name)
+        })
+        .collect()
+}
+
+fn normalise_template_name(name: &str) -> String {
+    let name = name.trim().replace('_', " ").to_lowercase();
+    name.strip_prefix("template:")
+        .map(|n| n.trim().to_string())
+        .unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_used_template_names() {
+        let mut names = used_template_names(
+            "{{Short description|Genre}}\n...\n{{Rock music}}\n{{Template:Heavy_metal}}",
+        )
+        .into_iter()
+        .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["heavy metal", "rock music", "short description"]);
+    }
+
+    #[test]
+    fn test_groups_from_parameters() {
+        let wikitext = "{{Navbox\n| title = [[Rock music]]\n| group1 = [[Hard rock]]\n| list1 = {{Navbox|child\n  | group1 = Subgenres\n  | list1 = [[Glam metal]] • [[Stoner rock]]\n  }}\n| group2 = Eras\n| list2 = [[Rock and roll]] • [[Rock music|1960s rock]]\n| list3 = [[Punk rock]]\n}}";
+        let parsed = pwt_configuration().parse(wikitext).unwrap();
+        let parameters = parsed
+            .nodes
+            .iter()
+            .find_map(navbox_template_parameters)
+            .unwrap();
+        let mut groups = vec![];
+        groups_from_parameters(parameters, "Rock music", None, &mut groups);
+        assert_eq!(
+            groups
+                .iter()
+                .map(|g| (g.name.as_str(), g.links.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "Hard rock › Subgenres",
+                    vec!["Glam metal".to_string(), "Stoner rock".to_string()]
+                ),
+                (
+                    "Eras",
+                    vec!["Rock and roll".to_string(), "Rock music".to_string()]
+                ),
+                ("Rock music", vec!["Punk rock".to_string()]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{PageName, ProcessedGenre};
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+/// What a node describes. Most nodes are genres, but the genre infobox is also used for
+/// pages about the music of a place or a movement.
+pub enum NodeKind {
+    #[default]
+    Genre,
+    /// The music of a place at a time (i.e. "Hip-hop in the Pacific Northwest")
+    Scene,
+    /// The music of a region or people (i.e. "Music of Latin America")
+    RegionalTradition,
+    /// A social or political movement with its own music (i.e. "Rock Against Communism")
+    Movement,
+}
+impl NodeKind {
+    pub fn is_genre(&self) -> bool {
+        *self == NodeKind::Genre
+    }
+}
+
+/// Classifies a page by its title, then by its categories. Pages without a processed genre
+/// (i.e. stubs) are only classified by their title.
+///
+/// Misclassified pages should be overridden in [`crate::data_patches::node_kinds`].
+pub fn classify(page: &PageName, processed_genre: Option<&ProcessedGenre>) -> NodeKind {
+    if let Some(kind) = classify_title(page.heading.as_deref().unwrap_or(&page.name)) {
+        return kind;
+    }
+    let Some(processed_genre) = processed_genre else {
+        return NodeKind::Genre;
+    };
+    classify_categories(&processed_genre.categories).unwrap_or(NodeKind::Genre)
+}
+
+fn classify_title(title: &str) -> Option<NodeKind> {
+    let lowercase = title.to_lowercase();
+    if lowercase.starts_with("music of ")
+        || lowercase.starts_with("traditional music of ")
+        || lowercase.contains(" music of ")
+    {
+        Some(NodeKind::RegionalTradition)
+    } else if lowercase.ends_with(" movement") || title.contains(" Against ") {
+        Some(NodeKind::Movement)
+    } else if lowercase.ends_with(" scene")
+        // "<genre> in <place>", i.e. "Punk rock in Italy"
+        || title
+            .split_once(" in ")
+            .is_some_and(|(_, place)| place.starts_with(char::is_uppercase))
+    {
+        Some(NodeKind::Scene)
+    } else {
+        None
+    }
+}
+
+fn classify_categories(categories: &[String]) -> Option<NodeKind> {
+    let has = |keyword: &str| {
+        categories
+            .iter()
+            .any(|category| category.to_lowercase().contains(keyword))
+    };
+    if has("music scenes") {
+        Some(NodeKind::Scene)
+    } else if has("movements") {
+        Some(NodeKind::Movement)
+    } else if has("traditions") {
+        Some(NodeKind::RegionalTradition)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_title() {
+        assert_eq!(
+            classify_title("Music of Latin America"),
+            Some(NodeKind::RegionalTradition)
+        );
+        assert_eq!(
+            classify_title("Hip-hop in the Pacific Northwest"),
+            Some(NodeKind::Scene)
+        );
+        assert_eq!(classify_title("Punk rock in Italy"), Some(NodeKind::Scene));
+        assert_eq!(classify_title("Madchester scene"), Some(NodeKind::Scene));
+        assert_eq!(
+            classify_title("Rock Against Communism"),
+            Some(NodeKind::Movement)
+        );
+        assert_eq!(classify_title("Drum and bass"), None);
+        assert_eq!(classify_title("Music hall"), None);
+    }
+
+    #[test]
+    fn test_classify_categories() {
+        assert_eq!(
+            classify_categories(&["Music scenes".to_string()]),
+            Some(NodeKind::Scene)
+        );
+        assert_eq!(
+            classify_categories(&["Political music genres".to_string()]),
+            None
+        );
+        assert_eq!(
+            classify_categories(&["Youth movements".to_string()]),
+            Some(NodeKind::Movement)
+        );
+    }
+
+    #[test]
+    fn test_classify_genre_with_scenes_but_no_origins() {
+        // An infobox without stylistic origins is a gap in the article, not a tradition
+        let processed_genre: ProcessedGenre = serde_json::from_value(serde_json::json!({
+            "name": "Bachata",
+            "page": "Bachata (music)",
+            "last_revision_date": "2025-01-01T00:00:00Z",
+            "stylistic_origins": [],
+            "derivatives": [],
+            "subgenres": [],
+            "fusion_genres": [],
+            "regional_scenes": ["[[Dominican Republic]]"],
+            "categories": ["Dominican Republic music genres"],
+        }))
+        .unwrap();
+        assert_eq!(
+            classify(&processed_genre.page, Some(&processed_genre)),
+            NodeKind::Genre
+        );
+    }
+}
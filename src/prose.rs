@@ -0,0 +1,490 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// The kind of relationship suggested by a cue phrase, from the point of view of the genre
+/// whose description the sentence is in.
+pub enum RelationType {
+    /// "a subgenre of", "a style of"
+    SubgenreOf,
+    /// "a fusion of", "combining"
+    FusionOf,
+    /// "emerged from", "influenced by"
+    DerivedFrom,
+    /// "subgenres include"
+    HasSubgenre,
+    /// "gave rise to", "a precursor to"
+    GaveRiseTo,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A candidate relationship with another genre, mined from a sentence of the description.
+pub struct ProseRelation {
+    pub ty: RelationType,
+    /// The unresolved link to the other genre
+    pub link: String,
+    /// The wikitext of the sentence the relationship was found in
+    pub sentence: String,
+    /// How likely the relationship is to be real, from 0 to 1, based on the strength of the
+    /// cue phrase and how far the link is from it
+    pub confidence: f32,
+}
+
+/// Cue phrases, the relationship they suggest and how strongly they suggest it.
+const CUES: &[(&str, RelationType, f32)] = &[
+    ("subgenre of", RelationType::SubgenreOf, 0.9),
+    ("sub-genre of", RelationType::SubgenreOf, 0.9),
+    ("offshoot of", RelationType::SubgenreOf, 0.8),
+    ("branch of", RelationType::SubgenreOf, 0.7),
+    ("style of", RelationType::SubgenreOf, 0.6),
+    ("form of", RelationType::SubgenreOf, 0.6),
+    ("variant of", RelationType::SubgenreOf, 0.6),
+    ("fusion of", RelationType::FusionOf, 0.9),
+    ("hybrid of", RelationType::FusionOf, 0.8),
+    ("fuses", RelationType::FusionOf, 0.8),
+    ("fusing", RelationType::FusionOf, 0.8),
+    ("combines", RelationType::FusionOf, 0.7),
+    ("combining", RelationType::FusionOf, 0.7),
+    ("blends", RelationType::FusionOf, 0.7),
+    ("blending", RelationType::FusionOf, 0.7),
+    ("mixture of", RelationType::FusionOf, 0.6),
+    ("mix of", RelationType::FusionOf, 0.6),
+    ("derived from", RelationType::DerivedFrom, 0.8),
+    ("derives from", RelationType::DerivedFrom, 0.8),
+    ("emerged from", RelationType::DerivedFrom, 0.8),
+    ("evolved from", RelationType::DerivedFrom, 0.8),
+    ("developed from", RelationType::DerivedFrom, 0.8),
+    ("developed out of", RelationType::DerivedFrom, 0.8),
+    ("grew out of", RelationType::DerivedFrom, 0.8),
+    ("originated from", RelationType::DerivedFrom, 0.7),
+    ("rooted in", RelationType::DerivedFrom, 0.7),
+    ("roots in", RelationType::DerivedFrom, 0.7),
+    ("influenced by", RelationType::DerivedFrom, 0.5),
+    ("inspired by", RelationType::DerivedFrom, 0.5),
+    ("subgenres include", RelationType::HasSubgenre, 0.8),
+    ("subgenres such as", RelationType::HasSubgenre, 0.8),
+    ("subgenres including", RelationType::HasSubgenre, 0.8),
+    ("gave rise to", RelationType::GaveRiseTo, 0.7),
+    ("precursor to", RelationType::GaveRiseTo, 0.7),
+    ("precursor of", RelationType::GaveRiseTo, 0.7),
+    ("forerunner of", RelationType::GaveRiseTo, 0.7),
+    ("paved the way for", RelationType::GaveRiseTo, 0.6),
+    ("led to", RelationType::GaveRiseTo, 0.4),
+];
+
+/// Words that commonly sit between a cue phrase and a list of genres without weakening it
+/// (i.e. "fusing elements of [[funk]] and [[rock music|rock]]").
+const FILLER_WORDS: &[&str] = &[
+    "a",
+    "an",
+    "and",
+    "as",
+    "both",
+    "elements",
+    "genres",
+    "including",
+    "music",
+    "of",
+    "or",
+    "other",
+    "such",
+    "the",
+    "various",
+    "well",
+    "with",
+];
+
+/// Candidates below this confidence are discarded.
+const MIN_CONFIDENCE: f32 = 0.2;
+
+/// Finds links to other pages in sentences of the description that follow a relationship
+/// cue phrase. The links are unresolved, so they may not be genres.
+pub fn extract_relations(description: &str) -> Vec<ProseRelation> {
+    let mut relations: Vec<ProseRelation> = vec![];
+    for sentence in sentences(description) {
+        let (plain, links) = plain_text_and_links(sentence);
+        // ASCII lowercasing keeps the byte offsets of the links valid
+        let lower = plain.to_ascii_lowercase();
+        let cues = find_cues(&lower);
+
+        for link in &links {
+            // The closest cue before the link applies to it
+            let Some(&(cue_end, ty, strength)) = cues
+                .iter()
+                .filter(|(cue_end, ..)| *cue_end <= link.start)
+                .max_by_key(|(cue_end, ..)| *cue_end)
+            else {
+                continue;
+            };
+
+            // Other links in between are part of the same list, so they don't count
+            let mut between = String::new();
+            let mut offset = cue_end;
+            for other in links
+                .iter()
+                .filter(|l| l.start >= cue_end && l.end <= link.start)
+            {
+                between.push_str(&lower[offset..other.start]);
+                offset = other.end;
+            }
+            between.push_str(&lower[offset..link.start]);
+            let words = between
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|w| !w.is_empty() && !FILLER_WORDS.contains(w))
+                .count();
+
+            let confidence = strength * 0.8f32.powi(words as i32);
+            if confidence < MIN_CONFIDENCE {
+                continue;
+            }
+            let confidence = (confidence * 100.0).round() / 100.0;
+
+            match relations
+                .iter_mut()
+                .find(|r| r.ty == ty && r.link == link.target)
+            {
+                Some(existing) if existing.confidence < confidence => {
+                    existing.confidence = confidence;
+                    existing.sentence = sentence.to_string();
+                }
+                Some(_) => {}
+                None => relations.push(ProseRelation {
+                    ty,
+                    link: link.target.clone(),
+                    sentence: sentence.to_string(),
+                    confidence,
+                }),
+            }
+        }
+    }
+    relations
+}
+
+/// Returns the end offset, relationship and strength of every cue phrase in `lower`.
+fn find_cues(lower: &str) -> Vec<(usize, RelationType, f32)> {
+    let is_boundary = |c: Option<char>| c.is_none_or(|c| !c.is_alphanumeric());
+    let mut cues = vec![];
+    for &(phrase, ty, strength) in CUES {
+        for (start, _) in lower.match_indices(phrase) {
+            let end = start + phrase.len();
+            if is_boundary(lower[..start].chars().next_back())
+                && is_boundary(lower[end..].chars().next())
+            {
+                cues.push((end, ty, strength));
+            }
+        }
+    }
+    cues
+}
+
+/// Splits wikitext into sentences, keeping links, templates and refs intact.
+/// Paragraph breaks always end a sentence.
+pub fn sentences(wikitext: &str) -> Vec<&str> {
+    split_sentences(wikitext, false)
+}
+
+/// Like [`sentences`], but every line break ends a sentence. This is for whole articles,
+/// where headings, list items and table rows are each on their own line.
+pub fn sentences_and_lines(wikitext: &str) -> Vec<&str> {
+    split_sentences(wikitext, true)
+}
+
+fn split_sentences(wikitext: &str, split_lines: bool) -> Vec<&str> {
+    const ABBREVIATIONS: &[&str] = &["c", "ca", "e.g", "i.e", "etc", "vs", "st", "no", "approx"];
+
+    let bytes = wikitext.as_bytes();
+    let mut sentences = vec![];
+    let mut push = |range: std::ops::Range<usize>| {
+        let sentence = wikitext[range].trim();
+        if !sentence.is_empty() {
+            sentences.push(sentence);
+        }
+    };
+
+    let mut depth = 0usize;
+    let mut sentence_start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match &bytes[i..] {
+            [b'[', b'[', ..] | [b'{', b'{', ..] => {
+                depth += 1;
+                i += 2;
+            }
+            [b']', b']', ..] | [b'}', b'}', ..] => {
+                depth = depth.saturating_sub(1);
+                i += 2;
+            }
+            [b'<', ..] => {
+                i = refs_end(wikitext, i).unwrap_or(i + 1);
+            }
+            [b'\n', b'\n', ..] if depth == 0 => {
+                push(sentence_start..i);
+                i += 2;
+                sentence_start = i;
+            }
+            [b'\n', ..] if depth == 0 && split_lines => {
+                push(sentence_start..i);
+                i += 1;
+                sentence_start = i;
+            }
+            [b'.' | b'!' | b'?', ..] if depth == 0 => {
+                let terminator = i;
+                i += 1;
+                // Refs attached to the end of a sentence belong to it
+                while starts_with_tag(&wikitext[i..], "ref") {
+                    i = element_end(wikitext, i, "ref");
+                }
+                let rest = &wikitext[i..];
+                let next = rest.trim_start();
+                let starts_sentence = next.is_empty()
+                    || (next.len() < rest.len()
+                        && next.starts_with(|c: char| {
+                            c.is_uppercase() || c.is_ascii_digit() || "[{'\"".contains(c)
+                        }));
+                let last_word = wikitext[sentence_start..terminator]
+                    .rsplit(char::is_whitespace)
+                    .next()
+                    .unwrap_or_default()
+                    .trim_start_matches(['(', '"', '\''])
+                    .to_lowercase();
+                let is_abbreviation = ABBREVIATIONS.contains(&last_word.as_str())
+                    // Initials, like the "J." in "J. Dilla"
+                    || (last_word.chars().count() == 1
+                        && last_word.chars().all(char::is_alphabetic));
+                if starts_sentence && !is_abbreviation {
+                    push(sentence_start..i);
+                    sentence_start = i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    push(sentence_start..wikitext.len());
+    sentences
+}
+
+/// A link in the plain text of a sentence.
+pub struct Link {
+    pub target: String,
+    /// Byte offsets of the link's text in the plain text
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Converts a sentence of wikitext to plain text, returning the links within it.
+/// Templates, refs, tags, files and categories are removed.
+pub fn plain_text_and_links(wikitext: &str) -> (String, Vec<Link>) {
+    let mut plain = String::new();
+    let mut links = vec![];
+    let mut i = 0;
+    while i < wikitext.len() {
+        let rest = &wikitext[i..];
+        if rest.starts_with("[[") {
+            let end = matching_end(wikitext, i, "[[", "]]");
+            let inner = &wikitext[i + 2..end];
+            let inner = inner.strip_suffix("]]").unwrap_or(inner);
+            let (target, text) = inner.split_once('|').unwrap_or((inner, inner));
+            let is_page = !target.split_once(':').is_some_and(|(namespace, _)| {
+                ["file", "image", "category"]
+                    .contains(&namespace.trim().to_ascii_lowercase().as_str())
+            });
+            if is_page {
+                let (text, _) = plain_text_and_links(text);
+                let start = plain.len();
+                plain.push_str(&text);
+                links.push(Link {
+                    target: target.trim().to_string(),
+                    start,
+                    end: plain.len(),
+                });
+            }
+            i = end;
+        } else if rest.starts_with("{{") {
+            i = matching_end(wikitext, i, "{{", "}}");
+        } else if let Some(end) = refs_end(wikitext, i) {
+            i = end;
+        } else if rest.starts_with("<!--") {
+            i = rest
+                .find("-->")
+                .map_or(wikitext.len(), |end| i + end + "-->".len());
+        } else if rest.starts_with('<') && rest.contains('>') {
+            i += rest.find('>').unwrap() + 1;
+        } else if rest.starts_with("''") {
+            i += rest.len() - rest.trim_start_matches('\'').len();
+        } else {
+            let c = rest.chars().next().unwrap();
+            plain.push(c);
+            i += c.len_utf8();
+        }
+    }
+    (plain, links)
+}
+
+/// Removes refs and comments from wikitext, so that it can be shown on its own.
+pub fn strip_refs_and_comments(wikitext: &str) -> String {
+    let mut output = String::new();
+    let mut i = 0;
+    while let Some(index) = wikitext[i..].find('<') {
+        let start = i + index;
+        output.push_str(&wikitext[i..start]);
+        let rest = &wikitext[start..];
+        i = if let Some(end) = refs_end(wikitext, start) {
+            end
+        } else if rest.starts_with("<!--") {
+            rest.find("-->")
+                .map_or(wikitext.len(), |end| start + end + "-->".len())
+        } else {
+            output.push('<');
+            start + 1
+        };
+    }
+    output.push_str(&wikitext[i..]);
+    output.trim().to_string()
+}
+
+/// Returns the offset after the `close` that matches the `open` at `start`, accounting for
+/// nesting, or the end of the string if it isn't closed.
+fn matching_end(wikitext: &str, start: usize, open: &str, close: &str) -> usize {
+    let mut depth = 0usize;
+    let mut i = start;
+    while i < wikitext.len() {
+        let rest = &wikitext[i..];
+        if rest.starts_with(open) {
+            depth += 1;
+            i += open.len();
+        } else if rest.starts_with(close) {
+            depth -= 1;
+            i += close.len();
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    wikitext.len()
+}
+
+/// Whether `rest` starts with a `name` tag, rather than a tag whose name starts with `name`
+/// (i.e. `<ref>` but not `<references>`).
+fn starts_with_tag(rest: &str, name: &str) -> bool {
+    rest.strip_prefix('<')
+        .and_then(|rest| rest.strip_prefix(name))
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/'))
+}
+
+/// Returns the offset after a ref or a `<references>` block starting at `start`, if there is
+/// one. List-defined references contain refs, so the block is skipped as a whole.
+fn refs_end(wikitext: &str, start: usize) -> Option<usize> {
+    let rest = &wikitext[start..];
+    ["ref", "references"]
+        .into_iter()
+        .find(|name| starts_with_tag(rest, name))
+        .map(|name| element_end(wikitext, start, name))
+}
+
+/// Returns the offset after the `name` element starting at `start`, whether it's
+/// self-closing or not.
+fn element_end(wikitext: &str, start: usize, name: &str) -> usize {
+    let rest = &wikitext[start..];
+    let Some(opening_end) = rest.find('>') else {
+        return wikitext.len();
+    };
+    if rest[..opening_end].ends_with('/') {
+        return start + opening_end + 1;
+    }
+    let closing = format!("</{name}>");
+    rest.find(&closing)
+        .map_or(wikitext.len(), |i| start + i + closing.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentences() {
+        assert_eq!(
+            sentences(
+                "'''Go-go''' emerged in [[Washington, D.C.]] in the mid-1960s.<ref>A. B.</ref> It was popularised by c. 1975 by [[Chuck Brown]]!\n\nIt is related to [[funk]]."
+            ),
+            vec![
+                "'''Go-go''' emerged in [[Washington, D.C.]] in the mid-1960s.<ref>A. B.</ref>",
+                "It was popularised by c. 1975 by [[Chuck Brown]]!",
+                "It is related to [[funk]].",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sentences_and_lines() {
+        let wikitext =
+            "== Origins ==\nIt emerged from [[funk]]\nand [[soul music|soul]].\n* [[Go-go]]";
+        assert_eq!(
+            sentences(wikitext),
+            vec!["== Origins ==\nIt emerged from [[funk]]\nand [[soul music|soul]].\n* [[Go-go]]"]
+        );
+        assert_eq!(
+            sentences_and_lines(wikitext),
+            vec![
+                "== Origins ==",
+                "It emerged from [[funk]]",
+                "and [[soul music|soul]].",
+                "* [[Go-go]]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strip_refs_and_comments() {
+        assert_eq!(
+            strip_refs_and_comments(
+                "[[Funk]] is a precursor to [[go-go]].<ref name=\"a\">Source</ref><!-- check --><ref name=b />"
+            ),
+            "[[Funk]] is a precursor to [[go-go]]."
+        );
+        assert_eq!(
+            strip_refs_and_comments(
+                "It spread to [[Japan]].<ref name=a />\n<references>\n<ref name=a>[[Source A]]</ref>\n<ref name=b>[[Source B]]</ref>\n</references>\n[[Category:Funk]]"
+            ),
+            "It spread to [[Japan]].\n\n[[Category:Funk]]"
+        );
+        assert_eq!(
+            strip_refs_and_comments("See <reference-list>.<ref>A</ref>"),
+            "See <reference-list>."
+        );
+    }
+
+    #[test]
+    fn test_plain_text_and_links() {
+        let (plain, links) = plain_text_and_links(
+            "'''Acid jazz''' is a [[Music genre|genre]]{{sfn|X|2001}} of [[jazz]][[File:X.jpg|thumb|A [[photo]]]].",
+        );
+        assert_eq!(plain, "Acid jazz is a genre of jazz.");
+        assert_eq!(
+            links
+                .iter()
+                .map(|l| (l.target.as_str(), &plain[l.start..l.end]))
+                .collect::<Vec<_>>(),
+            vec![("Music genre", "genre"), ("jazz", "jazz")]
+        );
+    }
+
+    #[test]
+    fn test_extract_relations() {
+        let relations = extract_relations(
+            "'''Jazz rap''' is a fusion of [[jazz]] and [[hip hop music|hip hop]] that emerged in the late 1980s. It was influenced by the poetry of [[The Last Poets]] and [[Gil Scott-Heron]].",
+        );
+        assert_eq!(
+            relations
+                .iter()
+                .map(|r| (r.ty, r.link.as_str(), r.confidence))
+                .collect::<Vec<_>>(),
+            vec![
+                (RelationType::FusionOf, "jazz", 0.9),
+                (RelationType::FusionOf, "hip hop music", 0.9),
+                (RelationType::DerivedFrom, "The Last Poets", 0.4),
+                (RelationType::DerivedFrom, "Gil Scott-Heron", 0.4),
+            ]
+        );
+    }
+}
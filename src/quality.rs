@@ -0,0 +1,248 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write as _,
+    path::Path,
+};
+
+use anyhow::Context;
+use parse_wiki_text_2 as pwt;
+use serde::{Deserialize, Serialize};
+use wikitext_util::{nodes_inner_text, InnerTextConfig};
+
+use crate::{citations, DumpMeta, PageName, ProcessedGenres};
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+/// A maintenance template that flags problems with an article's sourcing.
+pub enum MaintenanceTemplate {
+    Unreferenced,
+    CitationNeeded,
+    MoreCitationsNeeded,
+    OriginalResearch,
+}
+impl MaintenanceTemplate {
+    /// Matches a normalised template name, including its common redirects.
+    fn from_template_name(name: &str) -> Option<Self> {
+        match name {
+            "unreferenced" | "unsourced" | "no references" => Some(Self::Unreferenced),
+            "citation needed" | "cn" | "fact" | "cite needed" => Some(Self::CitationNeeded),
+            "more citations needed" | "refimprove" | "more sources" | "citations needed" => {
+                Some(Self::MoreCitationsNeeded)
+            }
+            "original research" | "or" => Some(Self::OriginalResearch),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+/// Signals of how reliable a genre's information is. Everything but the infobox coverage
+/// describes the article as a whole, so genres on the same page share them.
+pub struct Quality {
+    /// The length of the article's wikitext, in characters
+    pub article_length: usize,
+    /// The number of distinct refs in the article
+    pub reference_count: usize,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub maintenance_templates: BTreeSet<MaintenanceTemplate>,
+    /// The fraction of [`INFOBOX_FIELDS`] that are filled in for the genre, from 0 to 1
+    pub infobox_coverage: f32,
+}
+impl Quality {
+    /// The article-wide signals, without the infobox coverage.
+    pub fn from_article(wikitext: &str) -> Self {
+        let maintenance_templates = wikitext
+            .split("{{")
+            .skip(1)
+            .filter_map(|rest| {
+                let end = rest.find(['|', '}', '\n']).unwrap_or(rest.len());
+                let name = rest[..end].trim().replace('_', " ").to_lowercase();
+                MaintenanceTemplate::from_template_name(name.trim_start_matches("template:"))
+            })
+            .collect();
+        Self {
+            article_length: wikitext.chars().count(),
+            reference_count: citations::count_refs(wikitext),
+            maintenance_templates,
+            infobox_coverage: 0.0,
+        }
+    }
+
+    /// Sets the infobox coverage from the infobox's parameters.
+    pub fn with_infobox(mut self, parameters: &HashMap<String, &[pwt::Node]>) -> Self {
+        let filled = INFOBOX_FIELDS
+            .iter()
+            .filter(|field| {
+                parameters.get(**field).is_some_and(|nodes| {
+                    !nodes_inner_text(nodes, &InnerTextConfig::default()).is_empty()
+                })
+            })
+            .count();
+        self.infobox_coverage =
+            ((filled as f32 / INFOBOX_FIELDS.len() as f32) * 100.0).round() / 100.0;
+        self
+    }
+
+    /// An overall score from 0 to 1, used to find the weakest articles. Each signal is capped,
+    /// so that very long or heavily referenced articles don't dominate.
+    pub fn score(&self) -> f32 {
+        let length = (self.article_length as f32 / GOOD_ARTICLE_LENGTH as f32).min(1.0);
+        let references = (self.reference_count as f32 / GOOD_REFERENCE_COUNT as f32).min(1.0);
+        let maintenance = 1.0 - (self.maintenance_templates.len() as f32 / 2.0).min(1.0);
+        let score =
+            0.25 * length + 0.35 * references + 0.2 * maintenance + 0.2 * self.infobox_coverage;
+        (score * 100.0).round() / 100.0
+    }
+}
+
+/// The infobox fields that a well-described genre is expected to fill in.
+pub const INFOBOX_FIELDS: &[&str] = &[
+    "image",
+    "stylistic_origins",
+    "cultural_origins",
+    "instruments",
+    "derivatives",
+    "subgenres",
+    "fusiongenres",
+    "regional_scenes",
+    "local_scenes",
+    "other_topics",
+];
+
+/// The article length at which an article is considered fully developed for scoring.
+const GOOD_ARTICLE_LENGTH: usize = 20_000;
+/// The reference count at which an article is considered well-sourced for scoring.
+const GOOD_REFERENCE_COUNT: usize = 20;
+/// How many of the weakest genres to list in `quality.md`.
+const REPORT_LENGTH: usize = 200;
+
+#[derive(Debug, Serialize)]
+struct Entry<'a> {
+    page: &'a PageName,
+    score: f32,
+    #[serde(flatten)]
+    quality: &'a Quality,
+}
+
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    wikipedia_domain: &'a str,
+    dump_date: jiff::civil::Date,
+    genres: Vec<Entry<'a>>,
+}
+
+/// Writes the quality signals of every genre to `quality.json` and the weakest genres to
+/// `quality.md` in `reports_path`, ordered from weakest to strongest.
+pub fn produce_report(
+    start: std::time::Instant,
+    dump_meta: &DumpMeta,
+    reports_path: &Path,
+    processed_genres: &ProcessedGenres,
+) -> anyhow::Result<()> {
+    let mut genres = processed_genres
+        .0
+        .iter()
+        .filter_map(|(page, pg)| {
+            let quality = pg.quality.as_ref()?;
+            Some(Entry {
+                page,
+                score: quality.score(),
+                quality,
+            })
+        })
+        .collect::<Vec<_>>();
+    // Genres processed before quality signals were computed would all rank as the weakest
+    let missing_count = processed_genres.0.len() - genres.len();
+    if missing_count > 0 {
+        eprintln!(
+            "{missing_count} genres have no quality signals and were left out of the quality report; delete the processed genres to recompute them"
+        );
+    }
+    genres.sort_by(|a, b| a.score.total_cmp(&b.score).then(a.page.cmp(b.page)));
+
+    std::fs::create_dir_all(reports_path)?;
+    std::fs::write(
+        reports_path.join("quality.md"),
+        to_markdown(dump_meta, &genres),
+    )
+    .context("Failed to write quality report")?;
+    let genre_count = genres.len();
+    std::fs::write(
+        reports_path.join("quality.json"),
+        serde_json::to_string_pretty(&Report {
+            wikipedia_domain: &dump_meta.wikipedia_domain,
+            dump_date: dump_meta.dump_date,
+            genres,
+        })?,
+    )
+    .context("Failed to write quality report")?;
+
+    println!(
+        "{:.2}s: wrote quality report ({genre_count} genres)",
+        start.elapsed().as_secs_f32(),
+    );
+
+    Ok(())
+}
+
+fn to_markdown(dump_meta: &DumpMeta, genres: &[Entry]) -> String {
+    let mut output = String::new();
+    writeln!(output, "# Genre article quality report").unwrap();
+    writeln!(output).unwrap();
+    writeln!(
+        output,
+        "Generated from the `{}` dump of {}. The {REPORT_LENGTH} weakest genres are listed; every genre is included in `quality.json`.",
+        dump_meta.wikipedia_db_name, dump_meta.dump_date
+    )
+    .unwrap();
+    writeln!(output).unwrap();
+    writeln!(
+        output,
+        "| Genre | Score | Length | References | Infobox coverage | Maintenance templates |"
+    )
+    .unwrap();
+    writeln!(output, "| --- | --- | --- | --- | --- | --- |").unwrap();
+    for entry in genres.iter().take(REPORT_LENGTH) {
+        writeln!(
+            output,
+            "| [{}](https://{}/wiki/{}) | {:.2} | {} | {} | {:.0}% | {} |",
+            entry.page,
+            dump_meta.wikipedia_domain,
+            entry.page.linksafe(),
+            entry.score,
+            entry.quality.article_length,
+            entry.quality.reference_count,
+            entry.quality.infobox_coverage * 100.0,
+            entry
+                .quality
+                .maintenance_templates
+                .iter()
+                .map(|t| format!("{t:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_article() {
+        let quality = Quality::from_article(
+            "{{Multiple issues|\n{{Refimprove|date=May 2020}}\n{{Original research}}\n}}\n'''Nu-disco''' is a genre.<ref name=\"a\">A</ref> It is popular.{{cn|date=June 2021}}<ref name=\"a\" /><ref>B</ref>",
+        );
+        assert_eq!(quality.reference_count, 2);
+        assert_eq!(
+            quality.maintenance_templates,
+            BTreeSet::from_iter([
+                MaintenanceTemplate::CitationNeeded,
+                MaintenanceTemplate::MoreCitationsNeeded,
+                MaintenanceTemplate::OriginalResearch,
+            ])
+        );
+    }
+}
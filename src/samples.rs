@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use parse_wiki_text_2 as pwt;
+use serde::{Deserialize, Serialize};
+use wikitext_util::{nodes_inner_text, nodes_inner_wikitext, InnerTextConfig};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// A representative recording from a `{{Listen}}` or `{{Multi-listen item}}` template.
+pub struct Sample {
+    /// The file name without the `File:` prefix; the frontend is responsible for building a URL.
+    pub file_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Wikitext, as descriptions often contain links
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The heading of the section the sample was found in, if it wasn't in the lead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+/// Extracts the samples described by a template, if it is a listen template.
+///
+/// `{{Listen}}` supports multiple files through numbered parameters (`filename2`, `title2`, ...),
+/// while `{{Multi-listen item}}` describes a single file.
+pub fn from_template(
+    wikitext: &str,
+    template_name: &str,
+    parameters: &[pwt::Parameter],
+    section: Option<&str>,
+) -> Vec<Sample> {
+    let suffixes: Vec<String> = match template_name {
+        "listen" => std::iter::once(String::new())
+            .chain((2..=10).map(|i| i.to_string()))
+            .collect(),
+        "multi-listen item" => vec![String::new()],
+        _ => return vec![],
+    };
+
+    let named = parameters
+        .iter()
+        .filter_map(|p| {
+            let name = nodes_inner_text(p.name.as_deref()?, &InnerTextConfig::default());
+            Some((name, p.value.as_slice()))
+        })
+        .collect::<HashMap<_, _>>();
+
+    suffixes
+        .into_iter()
+        .filter_map(|suffix| {
+            let file_name = nodes_inner_text(
+                named.get(&format!("filename{suffix}"))?,
+                &InnerTextConfig::default(),
+            );
+            let file_name = file_name
+                .strip_prefix("File:")
+                .or_else(|| file_name.strip_prefix("file:"))
+                .unwrap_or(&file_name)
+                .trim();
+            if file_name.is_empty() {
+                return None;
+            }
+
+            let title = named
+                .get(&format!("title{suffix}"))
+                .map(|nodes| nodes_inner_text(nodes, &InnerTextConfig::default()))
+                .filter(|s| !s.is_empty());
+            let description = named
+                .get(&format!("description{suffix}"))
+                .map(|nodes| nodes_inner_wikitext(wikitext, nodes).trim().to_string())
+                .filter(|s| !s.is_empty());
+
+            Some(Sample {
+                file_name: file_name.to_string(),
+                title,
+                description,
+                section: section.map(|s| s.to_string()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(wikitext: &str) -> Vec<Sample> {
+        let mut output = vec![];
+        crate::for_each_template(wikitext, |template_name, parameters| {
+            output.extend(from_template(
+                wikitext,
+                template_name,
+                parameters,
+                Some("History"),
+            ))
+        });
+        output
+    }
+
+    #[test]
+    fn test_listen_with_multiple_files() {
+        assert_eq!(
+            samples(
+                "{{Listen|filename=Amen break.ogg|title=Amen break|description=The [[Amen break]], sampled heavily in [[jungle music|jungle]]|filename2=File:Think break.ogg|title2=Think}}"
+            ),
+            vec![
+                Sample {
+                    file_name: "Amen break.ogg".to_string(),
+                    title: Some("Amen break".to_string()),
+                    description: Some(
+                        "The [[Amen break]], sampled heavily in [[jungle music|jungle]]"
+                            .to_string()
+                    ),
+                    section: Some("History".to_string()),
+                },
+                Sample {
+                    file_name: "Think break.ogg".to_string(),
+                    title: Some("Think".to_string()),
+                    description: None,
+                    section: Some("History".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multi_listen_item() {
+        assert_eq!(
+            samples(
+                "{{Multi-listen start}}\n{{Multi-listen item|filename=Reggae sample.ogg|title=Reggae}}\n{{Multi-listen end}}"
+            ),
+            vec![Sample {
+                file_name: "Reggae sample.ogg".to_string(),
+                title: Some("Reggae".to_string()),
+                description: None,
+                section: Some("History".to_string()),
+            }]
+        );
+    }
+}
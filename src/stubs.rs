@@ -0,0 +1,554 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    io::BufRead as _,
+    path::Path,
+};
+
+use anyhow::Context;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    categories, data_patches, for_each_page_in_block, prose, AllRedirects, Config, GenrePages,
+    LinksToArticles, PageName, ProcessedGenres, WikitextHeader,
+};
+
+/// The minimum number of genres whose infoboxes must link to a page without an infobox for it
+/// to become a stub; pages that are only linked once are more often instruments or places.
+const MIN_LINKING_GENRES: usize = 2;
+
+/// Genres without an infobox, and the links that lead to them.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Stubs {
+    pub stubs: Vec<Stub>,
+    /// Lowercase links to each stub, in the same form as [`LinksToArticles`]
+    links: BTreeMap<String, PageName>,
+}
+impl Stubs {
+    /// Makes links to stubs resolvable, without overriding links to genres.
+    pub fn add_links(&self, links_to_articles: &mut LinksToArticles) {
+        for (link, page) in &self.links {
+            links_to_articles
+                .0
+                .entry(link.clone())
+                .or_insert_with(|| page.clone());
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A genre that isn't backed by an infobox, found through the genres that are.
+pub struct Stub {
+    pub page: PageName,
+    pub name: String,
+    pub short_description: Option<String>,
+    pub wikitext_description: Option<String>,
+    pub last_revision_date: jiff::Timestamp,
+    pub reasons: Vec<StubReason>,
+    /// The categories of the stub's page
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// Why a page was considered to be a genre.
+pub enum StubReason {
+    /// The page is linked from the infoboxes of these genres
+    InfoboxLinks { genres: Vec<PageName> },
+    /// The page is in a genre category
+    Category { category: String },
+    /// A redirect with the same name as the section leads to it
+    SectionRedirect { redirect: PageName },
+}
+
+/// Whether a category is a category of genres (i.e. "Electronic music genres", "Heavy metal
+/// genres" or "Brazilian styles of music"), rather than a category of something else's genres.
+pub fn is_genre_category(category: &str) -> bool {
+    const MUSIC_GENRE_SUFFIXES: &[&str] = &[
+        "music genres",
+        "metal genres",
+        "rock genres",
+        "punk genres",
+        "jazz genres",
+        "hip-hop genres",
+        "hip hop genres",
+        "pop music genres",
+    ];
+    let category = category.to_lowercase();
+    MUSIC_GENRE_SUFFIXES
+        .iter()
+        .any(|suffix| category.ends_with(suffix))
+        || category.contains("styles of music")
+}
+
+/// Whether a page is in any genre category.
+pub fn has_genre_category(wikitext: &str) -> bool {
+    categories::from_wikitext(wikitext)
+        .iter()
+        .any(|category| is_genre_category(category))
+}
+
+struct PageText {
+    timestamp: jiff::Timestamp,
+    text: String,
+}
+
+/// Finds genres that don't have an infobox: pages in genre categories, pages linked from the
+/// infoboxes of several genres, and sections of genre articles that have a redirect named
+/// after them. Pages that aren't already saved are extracted from the dump by looking them
+/// up in the index.
+///
+/// All redirects are needed to find these, so they're returned loaded for later use.
+#[allow(clippy::too_many_arguments)]
+pub fn discover(
+    start: std::time::Instant,
+    config: &Config,
+    stubs_path: &Path,
+    genres: &GenrePages,
+    stub_candidate_pages: &GenrePages,
+    processed_genres: &ProcessedGenres,
+    all_redirects: AllRedirects,
+) -> anyhow::Result<(Stubs, AllRedirects)> {
+    if stubs_path.is_file() {
+        let stubs: Stubs = serde_json::from_slice(&std::fs::read(stubs_path)?)?;
+        println!(
+            "{:.2}s: loaded {} stubs",
+            start.elapsed().as_secs_f32(),
+            stubs.stubs.len()
+        );
+        return Ok((stubs, all_redirects));
+    }
+
+    let all_redirects: HashMap<PageName, PageName> = all_redirects.try_into()?;
+    let pages_to_ignore = data_patches::pages_to_ignore()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let is_genre = |page: &PageName| {
+        processed_genres.0.contains_key(page)
+            || processed_genres
+                .0
+                .contains_key(&page.with_opt_heading(None))
+            || pages_to_ignore.contains(page)
+    };
+
+    // Pages linked from infoboxes that aren't genres themselves
+    let mut linking_genres: BTreeMap<PageName, BTreeSet<PageName>> = BTreeMap::new();
+    for (genre, processed_genre) in &processed_genres.0 {
+        for link in processed_genre
+            .stylistic_origins
+            .iter()
+            .chain(&processed_genre.derivatives)
+            .chain(&processed_genre.subgenres)
+            .chain(&processed_genre.fusion_genres)
+        {
+            let Some(page) = link_to_page(link) else {
+                continue;
+            };
+            let page = follow_redirects(&all_redirects, page);
+            if !is_genre(&page) {
+                linking_genres
+                    .entry(page)
+                    .or_default()
+                    .insert(genre.clone());
+            }
+        }
+    }
+
+    let mut texts = HashMap::new();
+    for (page, path) in stub_candidate_pages.iter().chain(genres.iter()) {
+        texts.insert(page.name.clone(), read_saved_page(path)?);
+    }
+    let missing = linking_genres
+        .iter()
+        .filter(|(page, genres)| {
+            genres.len() >= MIN_LINKING_GENRES && !texts.contains_key(&page.name)
+        })
+        .map(|(page, _)| page.name.clone())
+        .collect::<HashSet<_>>();
+    let fetched = fetch_pages(config, &missing)?;
+    println!(
+        "{:.2}s: fetched {} of {} pages linked from infoboxes",
+        start.elapsed().as_secs_f32(),
+        fetched.len(),
+        missing.len()
+    );
+    texts.extend(fetched);
+
+    let mut stubs: BTreeMap<PageName, Stub> = BTreeMap::new();
+    let mut add_reason = |page: &PageName, reason: StubReason| {
+        if let Some(stub) = stubs.get_mut(page) {
+            stub.reasons.push(reason);
+            return;
+        }
+        let Some(page_text) = texts.get(&page.name) else {
+            return;
+        };
+        if is_disambiguation(&page_text.text) {
+            return;
+        }
+        let Some(wikitext_description) = describe(&page_text.text, page.heading.as_deref()) else {
+            return;
+        };
+        stubs.insert(
+            page.clone(),
+            Stub {
+                page: page.clone(),
+                name: stub_name(page),
+                short_description: page
+                    .heading
+                    .is_none()
+                    .then(|| short_description(&page_text.text))
+                    .flatten(),
+                wikitext_description: Some(wikitext_description),
+                last_revision_date: page_text.timestamp,
+                reasons: vec![reason],
+                categories: categories::from_wikitext(&page_text.text),
+            },
+        );
+    };
+
+    for (page, _) in stub_candidate_pages.iter() {
+        if is_genre(page) {
+            continue;
+        }
+        let Some(page_text) = texts.get(&page.name) else {
+            continue;
+        };
+        for category in categories::from_wikitext(&page_text.text) {
+            if is_genre_category(&category) {
+                add_reason(page, StubReason::Category { category });
+            }
+        }
+    }
+    for (page, genres) in &linking_genres {
+        if genres.len() >= MIN_LINKING_GENRES || stub_candidate_pages.0.contains_key(page) {
+            add_reason(
+                page,
+                StubReason::InfoboxLinks {
+                    genres: genres.iter().cloned().collect(),
+                },
+            );
+        }
+    }
+    let mut section_redirects = all_redirects
+        .iter()
+        .filter_map(|(redirect, target)| {
+            let target = normalise_page(target);
+            let heading = target.heading.as_deref()?;
+            (names_match(&redirect.name, heading)
+                && !processed_genres.0.contains_key(&target)
+                && !pages_to_ignore.contains(&target))
+            .then(|| (redirect.clone(), target))
+        })
+        .collect::<Vec<_>>();
+    section_redirects.sort();
+    for (redirect, target) in section_redirects {
+        add_reason(&target, StubReason::SectionRedirect { redirect });
+    }
+
+    let mut links = BTreeMap::new();
+    for (page, genres) in &linking_genres {
+        if stubs.contains_key(page) {
+            for genre in genres {
+                let processed_genre = &processed_genres.0[genre];
+                for link in processed_genre
+                    .stylistic_origins
+                    .iter()
+                    .chain(&processed_genre.derivatives)
+                    .chain(&processed_genre.subgenres)
+                    .chain(&processed_genre.fusion_genres)
+                {
+                    if link_to_page(link).map(|l| follow_redirects(&all_redirects, l))
+                        == Some(page.clone())
+                    {
+                        links.insert(link.to_lowercase(), page.clone());
+                    }
+                }
+            }
+        }
+    }
+    for (redirect, target) in &all_redirects {
+        let target = normalise_page(target);
+        if stubs.contains_key(&target) {
+            links.insert(redirect.to_string().to_lowercase(), target);
+        }
+    }
+    for page in stubs.keys() {
+        links.insert(page.to_string().to_lowercase(), page.clone());
+    }
+
+    let stubs = Stubs {
+        stubs: stubs.into_values().collect(),
+        links,
+    };
+    std::fs::write(stubs_path, serde_json::to_string_pretty(&stubs)?)
+        .context("Failed to write stubs")?;
+    println!(
+        "{:.2}s: discovered {} stubs",
+        start.elapsed().as_secs_f32(),
+        stubs.stubs.len()
+    );
+
+    Ok((stubs, AllRedirects::InMemory(all_redirects)))
+}
+
+fn read_saved_page(path: &Path) -> anyhow::Result<PageText> {
+    let wikitext = std::fs::read_to_string(path)?;
+    let (header, text) = wikitext
+        .split_once('\n')
+        .with_context(|| format!("Missing header for {}", path.display()))?;
+    let header: WikitextHeader = serde_json::from_str(header)?;
+    Ok(PageText {
+        timestamp: header.timestamp,
+        text: text.to_string(),
+    })
+}
+
+/// Extracts the pages named `names` from the dump, using the index to only decompress the
+/// streams that contain them.
+fn fetch_pages(
+    config: &Config,
+    names: &HashSet<String>,
+) -> anyhow::Result<HashMap<String, PageText>> {
+    if names.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let index_file = std::fs::read(&config.wikipedia_index_path)
+        .context("Failed to open Wikipedia index file")?;
+    let index_file = std::io::BufReader::new(bzip2::bufread::BzDecoder::new(&index_file[..]));
+    let mut offsets = BTreeSet::<usize>::new();
+    for line in index_file.lines() {
+        let line = line.context("Failed to read line from Wikipedia index file")?;
+        // Lines are `offset:page_id:title`; titles can contain colons
+        let mut parts = line.splitn(3, ':');
+        let (Some(offset), Some(_), Some(title)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if names.contains(title) {
+            offsets.insert(offset.parse()?);
+        }
+    }
+
+    let dump_file = std::fs::File::open(&config.wikipedia_dump_path)
+        .context("Failed to open Wikipedia dump")?;
+    let dump_file =
+        unsafe { memmap2::Mmap::map(&dump_file).context("Failed to memory-map Wikipedia dump")? };
+    Ok(offsets
+        .into_iter()
+        .collect::<Vec<_>>()
+        .par_iter()
+        .flat_map_iter(|&offset| {
+            let mut pages = vec![];
+            for_each_page_in_block(&dump_file, offset, |title, timestamp, text| {
+                if !names.contains(title) {
+                    return;
+                }
+                let Ok(timestamp) = timestamp.parse() else {
+                    eprintln!("Failed to parse timestamp {timestamp} for {title}");
+                    return;
+                };
+                pages.push((
+                    title.to_string(),
+                    PageText {
+                        timestamp,
+                        text: text.to_string(),
+                    },
+                ));
+            });
+            pages
+        })
+        .collect())
+}
+
+/// Turns a link (i.e. `uk_garage#History`) into the page it's to.
+fn link_to_page(link: &str) -> Option<PageName> {
+    let link = link.trim();
+    if link.is_empty() || link.contains(':') {
+        return None;
+    }
+    Some(normalise_page(&match link.split_once('#') {
+        Some((name, heading)) => PageName::new(name, Some(heading.to_string())),
+        None => PageName::new(link, None),
+    }))
+}
+
+fn normalise_page(page: &PageName) -> PageName {
+    let mut name = page.name.trim().replace('_', " ");
+    if let Some(first) = name.chars().next() {
+        name.replace_range(..first.len_utf8(), &first.to_uppercase().to_string());
+    }
+    PageName::new(
+        name,
+        page.heading
+            .as_ref()
+            .map(|h| h.trim().replace('_', " "))
+            .filter(|h| !h.is_empty()),
+    )
+}
+
+/// Follows redirects until reaching an article. A redirect to a section replaces the heading
+/// of the link, if any.
+fn follow_redirects(all_redirects: &HashMap<PageName, PageName>, page: PageName) -> PageName {
+    let mut page = page;
+    // Bounded to avoid redirect loops
+    for _ in 0..5 {
+        let Some(target) = all_redirects.get(&page.with_opt_heading(None)) else {
+            break;
+        };
+        let target = normalise_page(target);
+        page = if target.heading.is_some() {
+            target
+        } else {
+            target.with_opt_heading(page.heading)
+        };
+    }
+    page
+}
+
+/// Whether a redirect's name matches the heading it leads to, ignoring case and any
+/// disambiguation (i.e. "Scouse house (music)" and "Scouse house").
+fn names_match(redirect: &str, heading: &str) -> bool {
+    strip_disambiguation(redirect).eq_ignore_ascii_case(heading.trim())
+}
+
+fn strip_disambiguation(name: &str) -> &str {
+    match name.rsplit_once(" (") {
+        Some((name, rest)) if rest.ends_with(')') => name,
+        _ => name,
+    }
+    .trim()
+}
+
+fn stub_name(page: &PageName) -> String {
+    match &page.heading {
+        Some(heading) => heading.clone(),
+        None => strip_disambiguation(&page.name).to_string(),
+    }
+}
+
+fn is_disambiguation(wikitext: &str) -> bool {
+    let wikitext = wikitext.to_ascii_lowercase();
+    [
+        "{{disambiguation",
+        "{{disambig",
+        "{{dab",
+        "{{set index",
+        "{{hndis",
+    ]
+    .iter()
+    .any(|template| wikitext.contains(template))
+}
+
+fn short_description(wikitext: &str) -> Option<String> {
+    const TEMPLATE: &str = "{{short description|";
+    // ASCII lowercasing preserves byte offsets
+    let start = wikitext.to_ascii_lowercase().find(TEMPLATE)? + TEMPLATE.len();
+    let end = start + wikitext[start..].find("}}")?;
+    Some(wikitext[start..end].trim().to_string())
+        .filter(|s| !s.is_empty() && !s.eq_ignore_ascii_case("none"))
+}
+
+/// The first paragraph of prose in the lead, or in the section with `heading`.
+fn describe(wikitext: &str, heading: Option<&str>) -> Option<String> {
+    let section = match heading {
+        Some(heading) => section(wikitext, heading)?,
+        None => wikitext
+            .find("\n==")
+            .map_or(wikitext, |end| &wikitext[..end])
+            .to_string(),
+    };
+    let section = prose::strip_refs_and_comments(&section);
+
+    fn is_prose_line(line: &str) -> bool {
+        let line = line.trim();
+        !line.is_empty()
+            && !line.starts_with(['{', '|', '}', '!', '=', '*', '#', ':', ';', '<', '_'])
+            && !["[[File:", "[[Image:", "[[Category:"]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+    }
+    section
+        .split("\n\n")
+        .map(|paragraph| {
+            paragraph
+                .lines()
+                .filter(|line| is_prose_line(line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .find(|paragraph| !paragraph.is_empty())
+}
+
+/// The wikitext of the section with `heading`, up to the next heading of the same or a
+/// higher level.
+fn section(wikitext: &str, heading: &str) -> Option<String> {
+    fn heading_level_and_text(line: &str) -> Option<(usize, &str)> {
+        let line = line.trim();
+        let level = line.chars().take_while(|&c| c == '=').count();
+        (level >= 2 && line.ends_with('=')).then(|| (level, line.trim_matches('=').trim()))
+    }
+
+    let mut lines = wikitext.lines();
+    let level = lines.find_map(|line| {
+        heading_level_and_text(line)
+            .filter(|(_, text)| text.eq_ignore_ascii_case(heading))
+            .map(|(level, _)| level)
+    })?;
+    Some(
+        lines
+            .take_while(|line| heading_level_and_text(line).is_none_or(|(l, _)| l > level))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_genre_category() {
+        assert!(is_genre_category("Electronic music genres"));
+        assert!(is_genre_category("Heavy metal genres"));
+        assert!(is_genre_category("Brazilian styles of music"));
+        assert!(!is_genre_category("Video game genres"));
+        assert!(!is_genre_category("Music of Brazil"));
+    }
+
+    #[test]
+    fn test_follow_redirects() {
+        let all_redirects = HashMap::from_iter([
+            (
+                PageName::new("Scouse House", None),
+                PageName::new("Scouse house", None),
+            ),
+            (
+                PageName::new("Scouse house", None),
+                PageName::new("UK_hard house", Some("Scouse house".to_string())),
+            ),
+        ]);
+        assert_eq!(
+            follow_redirects(&all_redirects, link_to_page("scouse House").unwrap()),
+            PageName::new("UK hard house", Some("Scouse house".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        let wikitext = "{{Short description|Genre of music}}\n{{Infobox musical artist\n| name = X\n}}\n'''Scouse''' is a genre.<ref>Cite</ref>\nIt is fast.\n\nMore.\n==History==\n===Scouse house===\n'''Scouse house''' is a [[UK hard house]] style.\n==See also==\n* [[Donk]]";
+        assert_eq!(
+            describe(wikitext, None).as_deref(),
+            Some("'''Scouse''' is a genre.\nIt is fast.")
+        );
+        assert_eq!(
+            describe(wikitext, Some("Scouse house")).as_deref(),
+            Some("'''Scouse house''' is a [[UK hard house]] style.")
+        );
+        assert_eq!(
+            short_description(wikitext).as_deref(),
+            Some("Genre of music")
+        );
+    }
+}
@@ -0,0 +1,577 @@
+use std::{collections::HashMap, path::Path};
+
+use anyhow::Context;
+use serde::Serialize;
+use wikitext_util::{nodes_inner_text, pwt_configuration, InnerTextConfig};
+
+use parse_wiki_text_2 as pwt;
+
+use crate::{
+    consistency::{Claim, Relationship, Status},
+    data_patches, nodes_recurse, sanitize_page_name, GenrePages, LinksToArticles, PageName,
+    ProcessedGenre, ProcessedGenres,
+};
+
+/// The infobox parameters that contain links to other genres or scenes.
+const LINK_FIELDS: &[&str] = &[
+    "stylistic_origins",
+    "derivatives",
+    "subgenres",
+    "fusiongenres",
+    "regional_scenes",
+    "local_scenes",
+];
+
+#[derive(Debug, Serialize)]
+/// The suggested edits to the infoboxes of a single article.
+struct ArticleEdits {
+    page: PageName,
+    /// An explanation of each change, in the order they appear in the article
+    changes: Vec<String>,
+    diff: String,
+}
+
+/// A replacement of a range of the article's wikitext.
+struct Change {
+    range: std::ops::Range<usize>,
+    replacement: String,
+    explanation: String,
+}
+
+/// Proposes fixes for the infoboxes of the processed genres, and writes them as unified diffs
+/// of each article's wikitext (one file per article in `suggested_edits`), along with an index
+/// in `suggested_edits.json`.
+///
+/// These are suggestions for editors to review and apply by hand; they are never applied
+/// automatically.
+pub fn produce_suggestions(
+    start: std::time::Instant,
+    reports_path: &Path,
+    genres: &GenrePages,
+    links_to_articles: &LinksToArticles,
+    processed_genres: &ProcessedGenres,
+    relationships: &[Relationship],
+) -> anyhow::Result<()> {
+    let mut genres_by_article: HashMap<&str, Vec<(&PageName, &ProcessedGenre)>> = HashMap::new();
+    for (page, processed_genre) in &processed_genres.0 {
+        genres_by_article
+            .entry(page.name.as_str())
+            .or_default()
+            .push((page, processed_genre));
+    }
+    let mut missing_by_page: HashMap<&PageName, Vec<&Claim>> = HashMap::new();
+    for claim in relationships
+        .iter()
+        .filter(|r| r.status == Status::OneSided)
+        .flat_map(|r| &r.missing)
+    {
+        missing_by_page.entry(&claim.page).or_default().push(claim);
+    }
+    let fixed_names = data_patches::fixed_already();
+    let pwt_configuration = pwt_configuration();
+
+    let suggested_edits_path = reports_path.join("suggested_edits");
+    if suggested_edits_path.is_dir() {
+        std::fs::remove_dir_all(&suggested_edits_path)?;
+    }
+    std::fs::create_dir_all(&suggested_edits_path)?;
+
+    let mut articles = genres.iter().collect::<Vec<_>>();
+    articles.sort();
+
+    let mut all_edits = vec![];
+    for (article, path) in articles {
+        let Some(article_genres) = genres_by_article.get(article.name.as_str()) else {
+            continue;
+        };
+
+        let wikitext = std::fs::read_to_string(path)?;
+        let Some((_header, wikitext)) = wikitext.split_once('\n') else {
+            continue;
+        };
+        let Ok(parsed_wikitext) =
+            pwt_configuration.parse_with_timeout(wikitext, std::time::Duration::from_secs(1))
+        else {
+            continue;
+        };
+        let infoboxes = parsed_wikitext
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                pwt::Node::Template {
+                    name,
+                    parameters,
+                    start,
+                    end,
+                    ..
+                } if nodes_inner_text(name, &InnerTextConfig::default()).to_lowercase()
+                    == "infobox music genre" =>
+                {
+                    Some((*start..*end, parameters.as_slice()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut infobox_changes = vec![];
+        for (page, processed_genre) in article_genres {
+            let Some((range, parameters)) = infoboxes.get(processed_genre.infobox_index) else {
+                continue;
+            };
+            let mut changes = vec![];
+            if let Some((_, name)) = fixed_names.get(article) {
+                changes.extend(name_change(wikitext, parameters, &name.0));
+            }
+            changes.extend(redirect_changes(wikitext, parameters, links_to_articles));
+            if let Some(claims) = missing_by_page.get(page) {
+                changes.extend(reciprocal_changes(
+                    wikitext,
+                    range.clone(),
+                    parameters,
+                    claims,
+                ));
+            }
+            if !changes.is_empty() {
+                infobox_changes.push((range.clone(), changes));
+            }
+        }
+        if infobox_changes.is_empty() {
+            continue;
+        }
+        infobox_changes.sort_by_key(|(range, _)| range.start);
+
+        let mut diff = format!("--- a/{article}\n+++ b/{article}\n");
+        let mut explanations = vec![];
+        // The difference in line numbers between the original and edited article, for
+        // the hunks that follow an edited infobox
+        let mut line_offset = 0isize;
+        for (range, mut changes) in infobox_changes {
+            changes.sort_by_key(|c| c.range.start);
+            explanations.extend(changes.iter().map(|c| c.explanation.clone()));
+
+            // Expand the infobox to whole lines so that the diff can be applied to the article
+            let start = wikitext[..range.start].rfind('\n').map_or(0, |i| i + 1);
+            let end = wikitext[range.end..]
+                .find('\n')
+                .map_or(wikitext.len(), |i| range.end + i + 1);
+            let old = &wikitext[start..end];
+            let mut new = old.to_string();
+            for change in changes.iter().rev() {
+                new.replace_range(
+                    change.range.start - start..change.range.end - start,
+                    &change.replacement,
+                );
+            }
+
+            let first_line = wikitext[..start].matches('\n').count() + 1;
+            diff.push_str(&unified_diff_hunks(
+                old,
+                &new,
+                first_line,
+                first_line.saturating_add_signed(line_offset),
+            ));
+            line_offset += new.lines().count() as isize - old.lines().count() as isize;
+        }
+
+        std::fs::write(
+            suggested_edits_path.join(format!("{}.diff", sanitize_page_name(article))),
+            format!(
+                "Suggested edits to the infobox of {article}:\n{}\n\n{diff}",
+                explanations
+                    .iter()
+                    .map(|e| format!("- {e}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        )
+        .with_context(|| format!("Failed to write suggested edits for {article}"))?;
+        all_edits.push(ArticleEdits {
+            page: article.clone(),
+            changes: explanations,
+            diff,
+        });
+    }
+
+    std::fs::write(
+        reports_path.join("suggested_edits.json"),
+        serde_json::to_string_pretty(&all_edits)?,
+    )
+    .context("Failed to write suggested edits")?;
+    println!(
+        "{:.2}s: suggested edits for {} articles ({} changes)",
+        start.elapsed().as_secs_f32(),
+        all_edits.len(),
+        all_edits.iter().map(|e| e.changes.len()).sum::<usize>()
+    );
+
+    Ok(())
+}
+
+/// Corrects the infobox's name to the name recorded in [`data_patches::fixed_already`],
+/// if it hasn't been corrected in this dump.
+fn name_change(wikitext: &str, parameters: &[pwt::Parameter], fixed_name: &str) -> Option<Change> {
+    let parameter = find_parameter(parameters, "name")?;
+    let name = nodes_inner_text(
+        &parameter.value,
+        &InnerTextConfig {
+            stop_after_br: true,
+        },
+    );
+    if name.is_empty() || name == fixed_name {
+        return None;
+    }
+    let start = parameter.start + wikitext[parameter.start..parameter.end].find(&name)?;
+    Some(Change {
+        range: start..start + name.len(),
+        replacement: fixed_name.to_string(),
+        explanation: format!("Correct the name from \"{name}\" to \"{fixed_name}\""),
+    })
+}
+
+/// Replaces links that go through a redirect with links to the article (or section) they
+/// resolve to, keeping the displayed text the same.
+fn redirect_changes(
+    wikitext: &str,
+    parameters: &[pwt::Parameter],
+    links_to_articles: &LinksToArticles,
+) -> Vec<Change> {
+    let mut changes = vec![];
+    for &field in LINK_FIELDS {
+        let Some(parameter) = find_parameter(parameters, field) else {
+            continue;
+        };
+        let mut links = vec![];
+        nodes_recurse(&parameter.value, &mut links, |links, node| {
+            if let pwt::Node::Link {
+                target, start, end, ..
+            } = node
+            {
+                links.push((target.to_string(), *start..*end));
+                false
+            } else {
+                true
+            }
+        });
+
+        for (target, range) in links {
+            let Some(article) = links_to_articles.map(&target) else {
+                continue;
+            };
+            if normalise_page_name(&target) == normalise_page_name(&article.to_string()) {
+                continue;
+            }
+            let Some(inner) = wikitext[range.clone()]
+                .strip_prefix("[[")
+                .and_then(|s| s.strip_suffix("]]"))
+            else {
+                continue;
+            };
+            let text = inner.split_once('|').map_or(inner, |(_, text)| text);
+            changes.push(Change {
+                range,
+                replacement: format!("[[{article}|{text}]]"),
+                explanation: format!(
+                    "Link directly to [[{article}]] instead of through the redirect [[{target}]] in `{field}`"
+                ),
+            });
+        }
+    }
+    changes
+}
+
+/// Adds the links that would make the one-sided relationships described by `claims`
+/// reciprocal, following the list style already used by each parameter.
+fn reciprocal_changes(
+    wikitext: &str,
+    infobox: std::ops::Range<usize>,
+    parameters: &[pwt::Parameter],
+    claims: &[&Claim],
+) -> Vec<Change> {
+    let mut claims_by_field: Vec<(&str, Vec<&Claim>)> = vec![];
+    for &claim in claims {
+        match claims_by_field.iter_mut().find(|(f, _)| *f == claim.field) {
+            Some((_, field_claims)) => field_claims.push(claim),
+            None => claims_by_field.push((claim.field.as_str(), vec![claim])),
+        }
+    }
+
+    let mut changes = vec![];
+    for (field, claims) in claims_by_field {
+        let links = claims.iter().map(|c| link_to(&c.other)).collect::<Vec<_>>();
+        let explanation = format!(
+            "Add {} to `{field}`, as {} already describe{} this relationship",
+            links.join(", "),
+            if claims.len() == 1 {
+                "its infobox"
+            } else {
+                "their infoboxes"
+            },
+            if claims.len() == 1 { "s" } else { "" },
+        );
+
+        let change = match find_parameter(parameters, field) {
+            Some(parameter) => {
+                let raw = &wikitext[parameter.start..parameter.end];
+                let Some(equals) = raw.find('=') else {
+                    continue;
+                };
+                let value_start = parameter.start + equals + 1;
+                let value = &wikitext[value_start..parameter.end];
+                if value.trim().is_empty() {
+                    let spaces = value.len() - value.trim_start_matches([' ', '\t']).len();
+                    Change {
+                        range: value_start..value_start + spaces,
+                        replacement: format!(" {}", links.join(", ")),
+                        explanation,
+                    }
+                } else {
+                    let (offset, insertion) = list_insertion(value.trim_end(), &links);
+                    let position = value_start + offset;
+                    Change {
+                        range: position..position,
+                        replacement: insertion,
+                        explanation,
+                    }
+                }
+            }
+            None => {
+                // Add a new parameter before the closing braces of the infobox
+                let position = infobox.end - 2;
+                let newline = if wikitext[..position].ends_with('\n') {
+                    ""
+                } else {
+                    "\n"
+                };
+                Change {
+                    range: position..position,
+                    replacement: format!("{newline}| {field} = {}\n", links.join(", ")),
+                    explanation,
+                }
+            }
+        };
+        changes.push(change);
+    }
+    changes
+}
+
+/// Returns where to insert `links` into the (trimmed) value of a list parameter, and the
+/// wikitext to insert, matching the style of the existing list.
+fn list_insertion(value: &str, links: &[String]) -> (usize, String) {
+    // ASCII lowercasing keeps the byte offsets of `<br>` valid in `value`
+    let lower = value.to_ascii_lowercase();
+    let is_list_template = [
+        "{{hlist",
+        "{{flatlist",
+        "{{plainlist",
+        "{{ubl",
+        "{{ubil",
+        "{{unbulleted list",
+    ]
+    .iter()
+    .any(|t| lower.starts_with(t))
+        && value.ends_with("}}");
+
+    if is_list_template {
+        let position = value.len() - 2;
+        let insertion = if value.contains("\n*") {
+            let items = links
+                .iter()
+                .map(|l| format!("* {l}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            if value[..position].ends_with('\n') {
+                format!("{items}\n")
+            } else {
+                format!("\n{items}")
+            }
+        } else {
+            links.iter().map(|l| format!("|{l}")).collect()
+        };
+        return (position, insertion);
+    }
+
+    let separator = if value.trim_start().starts_with('*') || value.contains("\n*") {
+        "\n* ".to_string()
+    } else if let Some(br_start) = lower.rfind("<br") {
+        let br_end = value[br_start..]
+            .find('>')
+            .map_or(value.len(), |i| br_start + i + 1);
+        value[br_start..br_end].to_string()
+    } else {
+        ", ".to_string()
+    };
+    (
+        value.len(),
+        links.iter().map(|l| format!("{separator}{l}")).collect(),
+    )
+}
+
+/// Produces the hunks of a unified diff between two sets of lines, with three lines of
+/// context, where the first line of each is at `old_first_line` and `new_first_line`
+/// (1-indexed) respectively.
+fn unified_diff_hunks(
+    old: &str,
+    new: &str,
+    old_first_line: usize,
+    new_first_line: usize,
+) -> String {
+    const CONTEXT: usize = 3;
+
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+
+    // Longest common subsequence of lines; infoboxes are small enough for this to be cheap
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', old[i]));
+            i += 1;
+        } else {
+            ops.push(('+', new[j]));
+            j += 1;
+        }
+    }
+
+    // Group changes that are close enough to share context into hunks
+    let changed = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != ' ')
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut hunks: Vec<std::ops::Range<usize>> = vec![];
+    for index in changed {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.end => hunk.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+
+    let mut output = String::new();
+    for hunk in hunks {
+        let lines_before = |op: char| ops[..hunk.start].iter().filter(|(o, _)| *o != op).count();
+        let lines_within = |op: char| ops[hunk.clone()].iter().filter(|(o, _)| *o != op).count();
+        output.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_first_line + lines_before('+'),
+            lines_within('+'),
+            new_first_line + lines_before('-'),
+            lines_within('-'),
+        ));
+        for (op, line) in &ops[hunk] {
+            output.push(*op);
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn find_parameter<'a>(
+    parameters: &'a [pwt::Parameter<'a>],
+    field: &str,
+) -> Option<&'a pwt::Parameter<'a>> {
+    parameters.iter().find(|p| {
+        p.name
+            .as_deref()
+            .is_some_and(|name| nodes_inner_text(name, &InnerTextConfig::default()) == field)
+    })
+}
+
+/// Normalises a page name for comparison: underscores are spaces, and the first letter
+/// is case-insensitive.
+fn normalise_page_name(name: &str) -> String {
+    let name = name.trim().replace('_', " ");
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
+    }
+}
+
+fn link_to(page: &PageName) -> String {
+    match &page.heading {
+        Some(heading) => format!("[[{page}|{heading}]]"),
+        None => format!("[[{}]]", page.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_insertion() {
+        let links = ["[[Funk]]".to_string()];
+        assert_eq!(
+            list_insertion("[[Disco]], [[Soul music|Soul]]", &links),
+            (30, ", [[Funk]]".to_string())
+        );
+        assert_eq!(
+            list_insertion("[[Disco]]<br />[[Soul music|Soul]]", &links),
+            (34, "<br />[[Funk]]".to_string())
+        );
+        assert_eq!(
+            list_insertion("{{hlist|[[Disco]]|[[Soul music|Soul]]}}", &links),
+            (37, "|[[Funk]]".to_string())
+        );
+        assert_eq!(
+            list_insertion(
+                "{{flatlist|\n* [[Disco]]\n* [[Soul music|Soul]]\n}}",
+                &links
+            ),
+            (46, "* [[Funk]]\n".to_string())
+        );
+        assert_eq!(
+            list_insertion("[[İstanbul pop]]<BR>[[Ⱥ]]", &links),
+            (27, "<BR>[[Funk]]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let old = "{{Infobox music genre\n| name = Go-go\n| stylistic_origins = [[Soul music|Soul]]\n| cultural_origins = 1970s\n}}\n";
+        let new = "{{Infobox music genre\n| name = Go-go\n| stylistic_origins = [[Soul music|Soul]], [[Funk]]\n| cultural_origins = 1970s\n}}\n";
+        assert_eq!(
+            unified_diff_hunks(old, new, 10, 12),
+            "@@ -10,5 +12,5 @@
+ {{Infobox music genre
+ | name = Go-go
+-| stylistic_origins = [[Soul music|Soul]]
++| stylistic_origins = [[Soul music|Soul]], [[Funk]]
+ | cultural_origins = 1970s
+ }}
+"
+        );
+    }
+
+    #[test]
+    fn test_normalise_page_name() {
+        assert_eq!(
+            normalise_page_name("hip_hop music"),
+            normalise_page_name("Hip hop music")
+        );
+        assert_ne!(
+            normalise_page_name("Hip hop"),
+            normalise_page_name("Hip hop music")
+        );
+    }
+}
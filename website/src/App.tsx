@@ -19,7 +19,11 @@ import {
 import commit from "./commit.json";
 import React from "react";
 
-import { dumpUrl, WikipediaMetaContext } from "./components/wikipedia/urls";
+import {
+  dumpUrl,
+  useWikiUrl,
+  WikipediaMetaContext,
+} from "./components/wikipedia/urls";
 
 import { Wikitext } from "./components/wikipedia/wikitexts/Wikitext";
 import { WikitextTruncateAtNewline } from "./components/wikipedia/wikitexts/WikitextTruncateAtNewline";
@@ -43,6 +47,7 @@ type Settings = {
       Derivative: boolean;
       Subgenre: boolean;
       FusionGenre: boolean;
+      Scene: boolean;
     };
   };
   simulation: SimulationParams;
@@ -56,6 +61,7 @@ const defaultSettings: Settings = {
       Derivative: true,
       Subgenre: true,
       FusionGenre: true,
+      Scene: true,
     },
   },
   simulation: defaultSimulationParams,
@@ -67,25 +73,122 @@ type Data = {
   dump_date: string;
   nodes: NodeData[];
   edges: EdgeData[];
+  relations: RelationData[];
+  fusions: FusionData[];
+  prose_edges: ProseEdgeData[];
+  navbox_groups: NavboxGroupData[];
   links_to_page_ids: Record<string, string>;
   max_degree: number;
 };
 type NodeData = {
   id: string;
   page_title: string;
+  short_description?: string;
   wikitext_description?: string;
+  lead_image?: { file_name: string; caption?: string; alt?: string };
   label: string;
-  last_revision_date: string;
+  aliases?: string[];
+  native_names?: {
+    text: string;
+    tag: string;
+    script?: string;
+    transliteration?: string;
+  }[];
+  last_revision_date?: string;
+  scene?: boolean;
+  stub?: boolean;
+  kind?: "Scene" | "RegionalTradition" | "Movement";
+  advisory?: "HateMusic" | "Extremist";
+  media_suppressed?: boolean;
+  tempo?: TempoRange;
+  time_signatures?: TimeSignature[];
+  quality?: Quality;
+  assessment_class?: "Stub" | "Start" | "C" | "B" | "GA" | "A" | "FA";
+  importance?: "Low" | "Mid" | "High" | "Top";
+  tags?: string[];
   mixes?:
     | { help_reason: string }
     | { playlist: string; note?: string }[]
     | { video: string; note?: string }[];
+  samples?: Sample[];
+  references?: Citation[];
+  description_references?: number[][];
   edges: number[];
+  relations?: number[];
+  fusions?: number[];
+  prose_edges?: number[];
+  navbox_groups?: number[];
+};
+type Sample = {
+  file_name: string;
+  title?: string;
+  description?: string;
+  section?: string;
+};
+type TempoRange = {
+  min: number;
+  max: number;
+};
+type TimeSignature = {
+  numerator: number;
+  denominator: number;
+};
+type Quality = {
+  article_length: number;
+  reference_count: number;
+  maintenance_templates?: (
+    | "Unreferenced"
+    | "CitationNeeded"
+    | "MoreCitationsNeeded"
+    | "OriginalResearch"
+  )[];
+  infobox_coverage: number;
+};
+type Citation = {
+  name?: string;
+  template?: string;
+  fields?: Record<string, string>;
+  wikitext?: string;
 };
 type EdgeData = {
+  source: string;
+  target: string;
+  ty: "Derivative" | "Subgenre" | "FusionGenre" | "Scene";
+  provenance?: {
+    page: string;
+    field: string;
+    position: number;
+    text_matched?: boolean;
+  }[];
+  inferred?: boolean;
+  text_matched?: boolean;
+  listings?: { page: string; section?: string }[];
+  listed?: boolean;
+  references?: { page: string; reference: number }[];
+  evidence?: { page: string; sentence: string }[];
+};
+type ProseEdgeData = {
   source: string;
   target: string;
   ty: "Derivative" | "Subgenre" | "FusionGenre";
+  page: string;
+  sentence: string;
+  confidence: number;
+};
+type FusionData = {
+  fusion: string;
+  members: string[];
+};
+type NavboxGroupData = {
+  navbox: string;
+  title: string;
+  group: string;
+  members: string[];
+};
+type RelationData = {
+  source: string;
+  target: string;
+  ty: "ConfusedWith" | "SeeAlso" | "MainArticle";
 };
 
 export const LinksToPageIdContext = createContext<Record<string, string>>({});
@@ -95,6 +198,8 @@ const subgenreColour = (saturation: number = 70, alpha: number = 1) =>
   `hsla(120, ${saturation}%, 60%, ${alpha})`;
 const fusionGenreColour = (saturation: number = 70, alpha: number = 1) =>
   `hsla(240, ${saturation}%, 60%, ${alpha})`;
+const sceneColour = (saturation: number = 70, alpha: number = 1) =>
+  `hsla(45, ${saturation}%, 60%, ${alpha})`;
 
 // Helper types for storing path information
 type NodeDistances = Map<string, number>;
@@ -250,12 +355,19 @@ function Graph({
           return "rgba(0, 0, 0, 0)";
         }
 
-        let colour = (saturation: number, alpha: number) =>
+        const baseColour =
           d.ty === "Derivative"
-            ? derivativeColour(saturation, alpha)
+            ? derivativeColour
             : d.ty === "Subgenre"
-            ? subgenreColour(saturation, alpha)
-            : fusionGenreColour(saturation, alpha);
+            ? subgenreColour
+            : d.ty === "FusionGenre"
+            ? fusionGenreColour
+            : sceneColour;
+        // Edges matched from unlinked text or only found in lists are less certain,
+        // so draw them fainter
+        const alphaScale = d.text_matched || d.listed ? 0.5 : 1;
+        let colour = (saturation: number, alpha: number) =>
+          baseColour(saturation, alpha * alphaScale);
 
         const selectedAlpha = 0.8;
         const selectedMinInfluenceAlpha = 0.4;
@@ -727,6 +839,13 @@ function ProjectInformation({
                 description:
                   "Genres that combine elements of this genre with other genres.",
               },
+              {
+                color: sceneColour(),
+                label: "Scene",
+                type: "Scene" as const,
+                description:
+                  "Regional and local scenes in which this genre is or was prominent.",
+              },
             ].map(({ color, label, type, description }) => (
               <div key={label} className="flex items-start gap-2">
                 <div>
@@ -869,6 +988,19 @@ function SelectedNodeInfo({
         { type: "text", content: ":" },
       ],
     },
+    {
+      type: "Scene" as const,
+      inbound: [
+        { type: "text", content: "Genres in this " },
+        { type: "emphasis", content: "scene" },
+        { type: "text", content: ":" },
+      ],
+      outbound: [
+        { type: "text", content: "Part of these " },
+        { type: "emphasis", content: "scenes" },
+        { type: "text", content: ":" },
+      ],
+    },
   ];
 
   const renderHeading = (
@@ -888,7 +1020,9 @@ function SelectedNodeInfo({
                     ? derivativeColour()
                     : type === "Subgenre"
                     ? subgenreColour()
-                    : fusionGenreColour(),
+                    : type === "FusionGenre"
+                    ? fusionGenreColour()
+                    : sceneColour(),
               }}
             >
               {part.content}
@@ -926,10 +1060,27 @@ function SelectedNodeInfo({
         <WikipediaLink pageTitle={node.page_title}>
           <h2 className="text-xl font-bold">{node.label}</h2>
         </WikipediaLink>
-        <small>
-          Last updated:{" "}
-          <em>{new Date(node.last_revision_date).toLocaleString()}</em>
-        </small>
+        {node.native_names && (
+          <div className="text-sm">
+            {node.native_names.map((name, i) => (
+              <div key={i}>
+                <span lang={name.tag}>{name.text}</span>
+                {name.transliteration && (
+                  <em className="text-neutral-400">
+                    {" "}
+                    ({name.transliteration})
+                  </em>
+                )}
+              </div>
+            ))}
+          </div>
+        )}
+        {node.last_revision_date && (
+          <small>
+            Last updated:{" "}
+            <em>{new Date(node.last_revision_date).toLocaleString()}</em>
+          </small>
+        )}
         <button
           className="w-full p-1 my-2 bg-neutral-800 hover:bg-neutral-700 text-white text-xs rounded"
           onClick={() => {
@@ -967,7 +1118,12 @@ function SelectedNodeInfo({
                 </React.Fragment>
               ))
             )
-          ) : (
+          ) : node.media_suppressed ? (
+            <Notice colour="blue">
+              Mixes and samples aren't shown for this genre, as it's associated
+              with hate music or extremism.
+            </Notice>
+          ) : node.scene || node.advisory ? null : (
             <Notice colour="red">
               There's no mix selected for this genre yet. If you know of a good
               mix or playlist that represents this genre well, please let me
@@ -975,6 +1131,8 @@ function SelectedNodeInfo({
             </Notice>
           )
         }
+        {node.lead_image && <LeadImage image={node.lead_image} />}
+        {node.samples && <SampleList samples={node.samples} />}
         {node.wikitext_description && (
           <WikitextTruncateAtNewline
             wikitext={node.wikitext_description}
@@ -1011,6 +1169,60 @@ function SelectedNodeInfo({
   );
 }
 
+function LeadImage({ image }: { image: NonNullable<NodeData["lead_image"]> }) {
+  const wikiUrl = useWikiUrl();
+  if (!wikiUrl) return null;
+
+  const fileName = image.file_name.replace(/ /g, "_");
+  return (
+    <figure className="mb-2">
+      <a href={`${wikiUrl}/File:${fileName}`}>
+        <img
+          src={`${wikiUrl}/Special:FilePath/${fileName}?width=300`}
+          alt={image.alt ?? ""}
+          className="w-full"
+          loading="lazy"
+        />
+      </a>
+      {image.caption && (
+        <figcaption className="text-sm text-neutral-400">
+          <Wikitext wikitext={image.caption} />
+        </figcaption>
+      )}
+    </figure>
+  );
+}
+
+function SampleList({ samples }: { samples: Sample[] }) {
+  const wikiUrl = useWikiUrl();
+  if (!wikiUrl) return null;
+
+  return (
+    <Collapsible title="Samples" defaultOpen={false}>
+      <div className="flex flex-col gap-2 mt-1">
+        {samples.map((sample, i) => (
+          <div key={i}>
+            <strong>{sample.title ?? sample.file_name}</strong>
+            {sample.section && (
+              <em className="text-neutral-400"> ({sample.section})</em>
+            )}
+            <audio
+              controls
+              preload="none"
+              className="w-full"
+              src={`${wikiUrl}/Special:FilePath/${sample.file_name.replace(
+                / /g,
+                "_"
+              )}`}
+            />
+            {sample.description && <Wikitext wikitext={sample.description} />}
+          </div>
+        ))}
+      </div>
+    </Collapsible>
+  );
+}
+
 function HelpNeededForMix({ reason }: { reason: string | null }) {
   return (
     <Notice colour="blue">
@@ -1167,7 +1379,11 @@ function Search({
     const normalizedFilter = normalizeStr(filter);
 
     return nodes
-      .filter((node) => normalizeStr(node.label).includes(normalizedFilter))
+      .filter((node) =>
+        [node.label, ...(node.aliases ?? [])].some((name) =>
+          normalizeStr(name).includes(normalizedFilter)
+        )
+      )
       .sort((a, b) => {
         // Sort by closest length first
         const lengthDiffA = Math.abs(a.label.length - filter.length);